@@ -0,0 +1,170 @@
+use crate::edns::Edns;
+use crate::message::Message;
+use crate::name::Name;
+use crate::question::Question;
+use crate::record::Record;
+use crate::types::{Opcode, Rcode, RecordClass, RecordType};
+
+/// Builds a [`Message`] section by section. Header counts are filled in
+/// from the sections by [`MessageBuilder::build`].
+#[derive(Debug, Clone)]
+pub struct MessageBuilder {
+    message: Message,
+    rcode: Rcode,
+    edns: Option<Edns>,
+}
+
+impl Message {
+    /// Starts a recursive query for `name` in class IN with a random ID.
+    pub fn query(name: Name, qtype: RecordType) -> MessageBuilder {
+        let mut message = Message::new();
+        message.header.id = random_id();
+        message.header.rd = true;
+        message.questions.push(Question {
+            qname: name,
+            qtype,
+            qclass: RecordClass::IN,
+        });
+        MessageBuilder::new(message)
+    }
+
+    /// Starts a response to `request`, copying the ID, opcode, RD and CD
+    /// bits and the question section as RFC 1035 section 4.1.1 and RFC 4035
+    /// section 3.1.6 require.
+    pub fn response_to(request: &Message) -> MessageBuilder {
+        let mut message = Message::new();
+        message.header.id = request.header.id;
+        message.header.qr = true;
+        message.header.opcode = request.header.opcode;
+        message.header.rd = request.header.rd;
+        message.header.cd = request.header.cd;
+        message.questions = request.questions.clone();
+        MessageBuilder::new(message)
+    }
+
+    pub fn builder() -> MessageBuilder {
+        MessageBuilder::new(Message::new())
+    }
+}
+
+impl MessageBuilder {
+    fn new(message: Message) -> Self {
+        MessageBuilder {
+            message,
+            rcode: Rcode::NoError,
+            edns: None,
+        }
+    }
+
+    pub fn id(mut self, id: u16) -> Self {
+        self.message.header.id = id;
+        self
+    }
+
+    pub fn opcode(mut self, opcode: Opcode) -> Self {
+        self.message.header.opcode = opcode;
+        self
+    }
+
+    pub fn aa(mut self, aa: bool) -> Self {
+        self.message.header.aa = aa;
+        self
+    }
+
+    pub fn rd(mut self, rd: bool) -> Self {
+        self.message.header.rd = rd;
+        self
+    }
+
+    pub fn ra(mut self, ra: bool) -> Self {
+        self.message.header.ra = ra;
+        self
+    }
+
+    pub fn ad(mut self, ad: bool) -> Self {
+        self.message.header.ad = ad;
+        self
+    }
+
+    pub fn cd(mut self, cd: bool) -> Self {
+        self.message.header.cd = cd;
+        self
+    }
+
+    /// Sets the response code. Extended codes are carried in the OPT record,
+    /// so they need [`MessageBuilder::edns`] as well.
+    pub fn rcode(mut self, rcode: Rcode) -> Self {
+        self.rcode = rcode;
+        self
+    }
+
+    pub fn question(mut self, question: Question) -> Self {
+        self.message.questions.push(question);
+        self
+    }
+
+    pub fn answer(mut self, record: Record) -> Self {
+        self.message.answers.push(record);
+        self
+    }
+
+    pub fn answers<I: IntoIterator<Item = Record>>(mut self, records: I) -> Self {
+        self.message.answers.extend(records);
+        self
+    }
+
+    pub fn authority(mut self, record: Record) -> Self {
+        self.message.authorities.push(record);
+        self
+    }
+
+    pub fn authorities<I: IntoIterator<Item = Record>>(mut self, records: I) -> Self {
+        self.message.authorities.extend(records);
+        self
+    }
+
+    /// Adds a record to the additional section. OPT records are ignored in
+    /// favour of [`MessageBuilder::edns`].
+    pub fn additional(mut self, record: Record) -> Self {
+        if record.rtype != RecordType::OPT {
+            self.message.additionals.push(record);
+        }
+        self
+    }
+
+    pub fn additionals<I: IntoIterator<Item = Record>>(mut self, records: I) -> Self {
+        for record in records {
+            self = self.additional(record);
+        }
+        self
+    }
+
+    /// Sets the EDNS parameters, written as an OPT record at the end of the
+    /// additional section.
+    pub fn edns(mut self, edns: Edns) -> Self {
+        self.edns = Some(edns);
+        self
+    }
+
+    pub fn build(self) -> Message {
+        let mut message = self.message;
+        if let Some(edns) = self.edns {
+            message.additionals.push(edns.to_record());
+        }
+        message.set_rcode(self.rcode);
+        message.update_counts();
+        message
+    }
+}
+
+/// A query ID that is hard for an off-path attacker to guess (RFC 5452),
+/// drawn from the operating system's random number generator.
+///
+/// # Panics
+///
+/// Panics if the operating system cannot supply random bytes.
+pub fn random_id() -> u16 {
+    let mut id = [0u8; 2];
+    getrandom::fill(&mut id).expect("OS random number generator failed");
+    u16::from_be_bytes(id)
+}
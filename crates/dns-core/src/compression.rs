@@ -1,85 +1,44 @@
+use crate::error::DecodeError;
+use crate::message_ref::NameRef;
+use crate::name::Name;
 use std::collections::HashMap;
-use std::io::{self, Read, Seek, Write};
+use std::io::{self, Cursor, Seek, Write};
 
+/// Pointers can only address the first 16 KiB of a message.
+const MAX_POINTER_OFFSET: u64 = 0x3FFF;
+
+/// Writes `name` using message compression. `compression_map` maps name
+/// suffixes to their offset from the start of the message.
 pub fn compress_name<W: Write + Seek>(
     writer: &mut W,
-    name: &str,
-    compression_map: &mut HashMap<String, u16>,
+    name: &Name,
+    compression_map: &mut HashMap<Name, u16>,
 ) -> io::Result<()> {
-    let labels: Vec<&str> = name.split('.').collect();
-    let mut current = String::new();
-    for label in labels.iter() {
-        if !current.is_empty() {
-            current.push('.');
-        }
-        current.push_str(label);
-        if let Some(&offset) = compression_map.get(&current) {
+    let labels: Vec<&[u8]> = name.labels().collect();
+    for (i, label) in labels.iter().enumerate() {
+        let suffix = name.suffix(labels.len() - i);
+        if let Some(&offset) = compression_map.get(&suffix) {
             // Write pointer
             let pointer = 0xC000 | offset;
             writer.write_all(&pointer.to_be_bytes())?;
             return Ok(());
-        } else {
-            compression_map.insert(
-                current.clone(),
-                writer.seek(io::SeekFrom::Current(0))? as u16,
-            );
-            let len = label.len();
-            if len > 63 {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    "Label too long",
-                ));
-            }
-            writer.write_all(&[len as u8])?;
-            writer.write_all(label.as_bytes())?;
         }
+        let position = writer.stream_position()?;
+        if position <= MAX_POINTER_OFFSET {
+            compression_map.insert(suffix, position as u16);
+        }
+        writer.write_all(&[label.len() as u8])?;
+        writer.write_all(label)?;
     }
     writer.write_all(&[0u8])?;
     Ok(())
 }
 
-pub fn decompress_name<R: Read>(
-    reader: &mut R,
-    compression_map: &HashMap<u16, String>,
-) -> io::Result<String> {
-    let mut name = String::new();
-
-    loop {
-        let len = {
-            let mut len_buf = [0u8; 1];
-            reader.read_exact(&mut len_buf)?;
-            len_buf[0]
-        };
-        if len == 0 {
-            break;
-        }
-        if (len & 0xC0) == 0xC0 {
-            let mut pointer_buf = [0u8; 1];
-            reader.read_exact(&mut pointer_buf)?;
-            let ptr = ((len & 0x3F) as u16) << 8 | (pointer_buf[0] as u16);
-            if let Some(p) = compression_map.get(&ptr) {
-                if !name.is_empty() {
-                    name.push('.');
-                }
-                name.push_str(p);
-                break;
-            } else {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    "Invalid compression pointer",
-                ));
-            }
-        } else {
-            let mut label = vec![0u8; len as usize];
-            reader.read_exact(&mut label)?;
-            if !name.is_empty() {
-                name.push('.');
-            }
-            name.push_str(
-                &String::from_utf8(label)
-                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid label"))?,
-            );
-        }
-    }
-    Ok(name)
+/// Reads a possibly compressed name, following pointers against the whole
+/// buffer behind the cursor. Pointers must point strictly backwards, which
+/// rules out both forward references and loops.
+pub fn decompress_name(reader: &mut Cursor<&[u8]>) -> Result<Name, DecodeError> {
+    let (name, end) = NameRef::parse(reader.get_ref(), reader.position() as usize)?;
+    reader.set_position(end as u64);
+    Ok(name.to_name())
 }
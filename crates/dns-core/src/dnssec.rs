@@ -0,0 +1,634 @@
+use crate::compression::decompress_name;
+use crate::encoding::{base32hex_encode, base64_encode, hex_encode};
+use crate::error::DecodeError;
+use crate::name::Name;
+use crate::types::RecordType;
+use crate::wire::{read_slice, read_u16, read_u32, read_u8, write_character_string};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::io::{self, Cursor, Write};
+
+/// DNSKEY and CDNSKEY RDATA (RFC 4034 section 2, RFC 7344).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dnskey {
+    pub flags: u16,
+    pub protocol: u8,
+    pub algorithm: u8,
+    pub public_key: Vec<u8>,
+}
+
+impl Dnskey {
+    pub const ZONE_KEY: u16 = 0x0100;
+    pub const SECURE_ENTRY_POINT: u16 = 0x0001;
+    pub const REVOKE: u16 = 0x0080;
+
+    pub fn is_zone_key(&self) -> bool {
+        self.flags & Self::ZONE_KEY != 0
+    }
+
+    pub fn is_secure_entry_point(&self) -> bool {
+        self.flags & Self::SECURE_ENTRY_POINT != 0
+    }
+
+    pub fn is_revoked(&self) -> bool {
+        self.flags & Self::REVOKE != 0
+    }
+
+    /// The key tag of RFC 4034 appendix B, computed over the RDATA.
+    pub fn key_tag(&self) -> u16 {
+        let mut rdata = Vec::with_capacity(4 + self.public_key.len());
+        rdata.extend_from_slice(&self.flags.to_be_bytes());
+        rdata.push(self.protocol);
+        rdata.push(self.algorithm);
+        rdata.extend_from_slice(&self.public_key);
+
+        let mut ac = 0u32;
+        for (i, &b) in rdata.iter().enumerate() {
+            ac += if i & 1 == 0 {
+                (b as u32) << 8
+            } else {
+                b as u32
+            };
+        }
+        ac += (ac >> 16) & 0xFFFF;
+        (ac & 0xFFFF) as u16
+    }
+
+    pub(crate) fn read(reader: &mut Cursor<&[u8]>, rdlength: u16) -> Result<Self, DecodeError> {
+        Ok(Dnskey {
+            flags: read_u16(reader)?,
+            protocol: read_u8(reader)?,
+            algorithm: read_u8(reader)?,
+            public_key: read_slice(reader, (rdlength as usize).saturating_sub(4))?.to_vec(),
+        })
+    }
+
+    pub(crate) fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.flags.to_be_bytes())?;
+        writer.write_all(&[self.protocol, self.algorithm])?;
+        writer.write_all(&self.public_key)
+    }
+}
+
+impl fmt::Display for Dnskey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.flags,
+            self.protocol,
+            self.algorithm,
+            base64_encode(&self.public_key)
+        )
+    }
+}
+
+/// DS and CDS RDATA (RFC 4034 section 5, RFC 7344).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ds {
+    pub key_tag: u16,
+    pub algorithm: u8,
+    pub digest_type: u8,
+    pub digest: Vec<u8>,
+}
+
+impl Ds {
+    pub const DIGEST_SHA1: u8 = 1;
+    pub const DIGEST_SHA256: u8 = 2;
+    pub const DIGEST_SHA384: u8 = 4;
+
+    pub(crate) fn read(reader: &mut Cursor<&[u8]>, rdlength: u16) -> Result<Self, DecodeError> {
+        Ok(Ds {
+            key_tag: read_u16(reader)?,
+            algorithm: read_u8(reader)?,
+            digest_type: read_u8(reader)?,
+            digest: read_slice(reader, (rdlength as usize).saturating_sub(4))?.to_vec(),
+        })
+    }
+
+    pub(crate) fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.key_tag.to_be_bytes())?;
+        writer.write_all(&[self.algorithm, self.digest_type])?;
+        writer.write_all(&self.digest)
+    }
+}
+
+impl fmt::Display for Ds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.key_tag,
+            self.algorithm,
+            self.digest_type,
+            hex_encode(&self.digest)
+        )
+    }
+}
+
+/// RRSIG RDATA (RFC 4034 section 3). Inception and expiration are seconds
+/// since the epoch, compared with serial number arithmetic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rrsig {
+    pub type_covered: RecordType,
+    pub algorithm: u8,
+    pub labels: u8,
+    pub original_ttl: u32,
+    pub expiration: u32,
+    pub inception: u32,
+    pub key_tag: u16,
+    pub signer_name: Name,
+    pub signature: Vec<u8>,
+}
+
+impl Rrsig {
+    pub(crate) fn read(reader: &mut Cursor<&[u8]>, rdlength: u16) -> Result<Self, DecodeError> {
+        let start = reader.position() as usize;
+        let type_covered = RecordType::from(read_u16(reader)?);
+        let algorithm = read_u8(reader)?;
+        let labels = read_u8(reader)?;
+        let original_ttl = read_u32(reader)?;
+        let expiration = read_u32(reader)?;
+        let inception = read_u32(reader)?;
+        let key_tag = read_u16(reader)?;
+        let signer_name = decompress_name(reader)?;
+        let remaining = (start + rdlength as usize).saturating_sub(reader.position() as usize);
+        let signature = read_slice(reader, remaining)?.to_vec();
+        Ok(Rrsig {
+            type_covered,
+            algorithm,
+            labels,
+            original_ttl,
+            expiration,
+            inception,
+            key_tag,
+            signer_name,
+            signature,
+        })
+    }
+
+    /// Writes the RDATA. The signer name is never compressed.
+    pub(crate) fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&u16::from(self.type_covered).to_be_bytes())?;
+        writer.write_all(&[self.algorithm, self.labels])?;
+        writer.write_all(&self.original_ttl.to_be_bytes())?;
+        writer.write_all(&self.expiration.to_be_bytes())?;
+        writer.write_all(&self.inception.to_be_bytes())?;
+        writer.write_all(&self.key_tag.to_be_bytes())?;
+        self.signer_name.write(writer)?;
+        writer.write_all(&self.signature)
+    }
+}
+
+impl fmt::Display for Rrsig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {} {} {} {} {}",
+            self.type_covered,
+            self.algorithm,
+            self.labels,
+            self.original_ttl,
+            format_timestamp(self.expiration),
+            format_timestamp(self.inception),
+            self.key_tag,
+            self.signer_name,
+            base64_encode(&self.signature)
+        )
+    }
+}
+
+/// Formats a signature time as `YYYYMMDDHHmmSS` in UTC.
+pub fn format_timestamp(time: u32) -> String {
+    let days = (time / 86400) as i64;
+    let seconds = time % 86400;
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{:04}{:02}{:02}{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Parses a signature time given either as `YYYYMMDDHHmmSS` or as a plain
+/// number of seconds since the epoch (RFC 4034 section 3.2).
+pub fn parse_timestamp(text: &str) -> Option<u32> {
+    if !text.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    if text.len() != 14 {
+        return text.parse().ok();
+    }
+    let field = |range: std::ops::Range<usize>| text[range].parse::<u32>().ok();
+    let (year, month, day) = (field(0..4)?, field(4..6)?, field(6..8)?);
+    let (hour, minute, second) = (field(8..10)?, field(10..12)?, field(12..14)?);
+    if !(1..=12).contains(&month)
+        || day == 0
+        || day > days_in_month(year, month)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return None;
+    }
+    let days = days_from_civil(year as i64, month, day);
+    let time = days * 86400 + (hour * 3600 + minute * 60 + second) as i64;
+    u32::try_from(time).ok()
+}
+
+fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Conversions between days since 1970-01-01 and the proleptic Gregorian
+// calendar, after Howard Hinnant's `chrono`-compatible algorithms.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// The set of types present at an owner name, encoded as the window blocks
+/// of RFC 4034 section 4.1.2.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TypeBitmap {
+    types: BTreeSet<RecordType>,
+}
+
+impl TypeBitmap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, rtype: RecordType) -> bool {
+        self.types.insert(rtype)
+    }
+
+    pub fn contains(&self, rtype: RecordType) -> bool {
+        self.types.contains(&rtype)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+
+    /// Iterates over the types in increasing numeric order.
+    pub fn iter(&self) -> impl Iterator<Item = RecordType> + '_ {
+        self.types.iter().copied()
+    }
+
+    /// Reads `len` bytes of window blocks, rejecting empty or oversized
+    /// blocks, windows out of order and trailing zero octets.
+    pub(crate) fn read(
+        reader: &mut Cursor<&[u8]>,
+        rtype: RecordType,
+        len: usize,
+    ) -> Result<Self, DecodeError> {
+        let start = reader.position() as usize;
+        let mut bitmap = TypeBitmap::new();
+        let mut last_window = None;
+        while (reader.position() as usize) < start + len {
+            let offset = reader.position() as usize;
+            let window = read_u8(reader)?;
+            let block_len = read_u8(reader)?;
+            let block = read_slice(reader, block_len as usize)?;
+            if last_window.is_some_and(|last| window <= last)
+                || !(1..=32).contains(&block_len)
+                || block.last() == Some(&0)
+            {
+                return Err(DecodeError::InvalidRData {
+                    offset,
+                    rtype,
+                    reason: "invalid type bitmap",
+                });
+            }
+            last_window = Some(window);
+            for (i, &octet) in block.iter().enumerate() {
+                for bit in 0..8 {
+                    if octet & (0x80 >> bit) != 0 {
+                        let value = (window as u16) << 8 | (i * 8 + bit) as u16;
+                        bitmap.insert(RecordType::from(value));
+                    }
+                }
+            }
+        }
+        Ok(bitmap)
+    }
+
+    pub(crate) fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut types = self.iter().map(u16::from).peekable();
+        while let Some(&first) = types.peek() {
+            let window = (first >> 8) as u8;
+            let mut block = [0u8; 32];
+            let mut block_len = 0;
+            while let Some(value) = types.next_if(|&value| (value >> 8) as u8 == window) {
+                let bit = (value & 0xFF) as usize;
+                block[bit / 8] |= 0x80 >> (bit % 8);
+                block_len = bit / 8 + 1;
+            }
+            writer.write_all(&[window, block_len as u8])?;
+            writer.write_all(&block[..block_len])?;
+        }
+        Ok(())
+    }
+}
+
+impl FromIterator<RecordType> for TypeBitmap {
+    fn from_iter<I: IntoIterator<Item = RecordType>>(iter: I) -> Self {
+        TypeBitmap {
+            types: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl fmt::Display for TypeBitmap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, rtype) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{}", rtype)?;
+        }
+        Ok(())
+    }
+}
+
+/// NSEC RDATA (RFC 4034 section 4).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Nsec {
+    pub next_domain: Name,
+    pub types: TypeBitmap,
+}
+
+impl Nsec {
+    pub(crate) fn read(reader: &mut Cursor<&[u8]>, rdlength: u16) -> Result<Self, DecodeError> {
+        let start = reader.position() as usize;
+        let next_domain = decompress_name(reader)?;
+        let remaining = (start + rdlength as usize).saturating_sub(reader.position() as usize);
+        let types = TypeBitmap::read(reader, RecordType::NSEC, remaining)?;
+        Ok(Nsec { next_domain, types })
+    }
+
+    /// Writes the RDATA. The next domain name is never compressed.
+    pub(crate) fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.next_domain.write(writer)?;
+        self.types.write(writer)
+    }
+}
+
+impl fmt::Display for Nsec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.next_domain)?;
+        if !self.types.is_empty() {
+            write!(f, " {}", self.types)?;
+        }
+        Ok(())
+    }
+}
+
+/// NSEC3 RDATA (RFC 5155 section 3).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Nsec3 {
+    pub hash_algorithm: u8,
+    pub flags: u8,
+    pub iterations: u16,
+    pub salt: Vec<u8>,
+    pub next_hashed_owner: Vec<u8>,
+    pub types: TypeBitmap,
+}
+
+impl Nsec3 {
+    pub const OPT_OUT: u8 = 0x01;
+
+    pub fn opt_out(&self) -> bool {
+        self.flags & Self::OPT_OUT != 0
+    }
+
+    pub(crate) fn read(reader: &mut Cursor<&[u8]>, rdlength: u16) -> Result<Self, DecodeError> {
+        let start = reader.position() as usize;
+        let hash_algorithm = read_u8(reader)?;
+        let flags = read_u8(reader)?;
+        let iterations = read_u16(reader)?;
+        let salt_len = read_u8(reader)?;
+        let salt = read_slice(reader, salt_len as usize)?.to_vec();
+        let hash_len = read_u8(reader)?;
+        let next_hashed_owner = read_slice(reader, hash_len as usize)?.to_vec();
+        let remaining = (start + rdlength as usize).saturating_sub(reader.position() as usize);
+        let types = TypeBitmap::read(reader, RecordType::NSEC3, remaining)?;
+        Ok(Nsec3 {
+            hash_algorithm,
+            flags,
+            iterations,
+            salt,
+            next_hashed_owner,
+            types,
+        })
+    }
+
+    pub(crate) fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&[self.hash_algorithm, self.flags])?;
+        writer.write_all(&self.iterations.to_be_bytes())?;
+        write_character_string(writer, &self.salt)?;
+        write_character_string(writer, &self.next_hashed_owner)?;
+        self.types.write(writer)
+    }
+}
+
+impl fmt::Display for Nsec3 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {}",
+            self.hash_algorithm,
+            self.flags,
+            self.iterations,
+            Salt(&self.salt),
+            base32hex_encode(&self.next_hashed_owner)
+        )?;
+        if !self.types.is_empty() {
+            write!(f, " {}", self.types)?;
+        }
+        Ok(())
+    }
+}
+
+/// NSEC3PARAM RDATA (RFC 5155 section 4).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Nsec3Param {
+    pub hash_algorithm: u8,
+    pub flags: u8,
+    pub iterations: u16,
+    pub salt: Vec<u8>,
+}
+
+impl Nsec3Param {
+    pub(crate) fn read(reader: &mut Cursor<&[u8]>) -> Result<Self, DecodeError> {
+        let hash_algorithm = read_u8(reader)?;
+        let flags = read_u8(reader)?;
+        let iterations = read_u16(reader)?;
+        let salt_len = read_u8(reader)?;
+        let salt = read_slice(reader, salt_len as usize)?.to_vec();
+        Ok(Nsec3Param {
+            hash_algorithm,
+            flags,
+            iterations,
+            salt,
+        })
+    }
+
+    pub(crate) fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&[self.hash_algorithm, self.flags])?;
+        writer.write_all(&self.iterations.to_be_bytes())?;
+        write_character_string(writer, &self.salt)
+    }
+}
+
+impl fmt::Display for Nsec3Param {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.hash_algorithm,
+            self.flags,
+            self.iterations,
+            Salt(&self.salt)
+        )
+    }
+}
+
+/// An NSEC3 salt in presentation format, where `-` stands for no salt.
+struct Salt<'a>(&'a [u8]);
+
+impl fmt::Display for Salt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            f.write_str("-")
+        } else {
+            f.write_str(&hex_encode(self.0))
+        }
+    }
+}
+
+/// ZONEMD RDATA (RFC 8976).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Zonemd {
+    pub serial: u32,
+    pub scheme: u8,
+    pub hash_algorithm: u8,
+    pub digest: Vec<u8>,
+}
+
+impl Zonemd {
+    pub const SCHEME_SIMPLE: u8 = 1;
+    pub const HASH_SHA384: u8 = 1;
+    pub const HASH_SHA512: u8 = 2;
+
+    pub(crate) fn read(reader: &mut Cursor<&[u8]>, rdlength: u16) -> Result<Self, DecodeError> {
+        Ok(Zonemd {
+            serial: read_u32(reader)?,
+            scheme: read_u8(reader)?,
+            hash_algorithm: read_u8(reader)?,
+            digest: read_slice(reader, (rdlength as usize).saturating_sub(6))?.to_vec(),
+        })
+    }
+
+    pub(crate) fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.serial.to_be_bytes())?;
+        writer.write_all(&[self.scheme, self.hash_algorithm])?;
+        writer.write_all(&self.digest)
+    }
+}
+
+impl fmt::Display for Zonemd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.serial,
+            self.scheme,
+            self.hash_algorithm,
+            hex_encode(&self.digest)
+        )
+    }
+}
+
+/// CSYNC RDATA (RFC 7477).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Csync {
+    pub serial: u32,
+    pub flags: u16,
+    pub types: TypeBitmap,
+}
+
+impl Csync {
+    pub const IMMEDIATE: u16 = 0x0001;
+    pub const SOA_MINIMUM: u16 = 0x0002;
+
+    pub(crate) fn read(reader: &mut Cursor<&[u8]>, rdlength: u16) -> Result<Self, DecodeError> {
+        let serial = read_u32(reader)?;
+        let flags = read_u16(reader)?;
+        let types = TypeBitmap::read(
+            reader,
+            RecordType::CSYNC,
+            (rdlength as usize).saturating_sub(6),
+        )?;
+        Ok(Csync {
+            serial,
+            flags,
+            types,
+        })
+    }
+
+    pub(crate) fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.serial.to_be_bytes())?;
+        writer.write_all(&self.flags.to_be_bytes())?;
+        self.types.write(writer)
+    }
+}
+
+impl fmt::Display for Csync {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.serial, self.flags)?;
+        if !self.types.is_empty() {
+            write!(f, " {}", self.types)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+use crate::encoding::hex_encode;
+use crate::error::DecodeError;
+use crate::name::Name;
+use crate::record::{RData, Record};
+use crate::types::{RecordClass, RecordType};
+use std::fmt;
+
+/// An EDNS(0) option (RFC 6891 section 6.1.2).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EdnsOption {
+    pub code: u16,
+    pub data: Vec<u8>,
+}
+
+impl EdnsOption {
+    pub const NSID: u16 = 3;
+    pub const CLIENT_SUBNET: u16 = 8;
+    pub const EXPIRE: u16 = 9;
+    pub const COOKIE: u16 = 10;
+    pub const TCP_KEEPALIVE: u16 = 11;
+    pub const PADDING: u16 = 12;
+    pub const EXTENDED_ERROR: u16 = 15;
+
+    pub fn mnemonic(&self) -> Option<&'static str> {
+        Some(match self.code {
+            Self::NSID => "NSID",
+            Self::CLIENT_SUBNET => "CLIENT-SUBNET",
+            Self::EXPIRE => "EXPIRE",
+            Self::COOKIE => "COOKIE",
+            Self::TCP_KEEPALIVE => "TCP-KEEPALIVE",
+            Self::PADDING => "PADDING",
+            Self::EXTENDED_ERROR => "EDE",
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for EdnsOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.mnemonic() {
+            Some(mnemonic) => write!(f, "{}: {}", mnemonic, hex_encode(&self.data)),
+            None => write!(f, "OPT={}: {}", self.code, hex_encode(&self.data)),
+        }
+    }
+}
+
+/// The EDNS(0) parameters carried in the class, TTL and RDATA of an OPT
+/// pseudo-record (RFC 6891 section 6.1.3).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Edns {
+    pub udp_payload_size: u16,
+    pub ext_rcode: u8,
+    pub version: u8,
+    pub flags: u16,
+    pub options: Vec<EdnsOption>,
+}
+
+impl Default for Edns {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Edns {
+    /// The DO bit, requesting DNSSEC records (RFC 3225).
+    pub const DNSSEC_OK: u16 = 0x8000;
+
+    /// EDNS version 0 advertising a 1232 byte payload, the size recommended
+    /// to avoid IP fragmentation.
+    pub fn new() -> Self {
+        Edns {
+            udp_payload_size: 1232,
+            ext_rcode: 0,
+            version: 0,
+            flags: 0,
+            options: Vec::new(),
+        }
+    }
+
+    pub fn dnssec_ok(&self) -> bool {
+        self.flags & Self::DNSSEC_OK != 0
+    }
+
+    pub fn set_dnssec_ok(&mut self, dnssec_ok: bool) {
+        if dnssec_ok {
+            self.flags |= Self::DNSSEC_OK;
+        } else {
+            self.flags &= !Self::DNSSEC_OK;
+        }
+    }
+
+    pub fn option(&self, code: u16) -> Option<&EdnsOption> {
+        self.options.iter().find(|o| o.code == code)
+    }
+
+    /// Decodes an OPT record. Error offsets are relative to the RDATA.
+    pub fn from_record(record: &Record) -> Result<Self, DecodeError> {
+        let invalid = |offset, reason| DecodeError::InvalidRData {
+            offset,
+            rtype: RecordType::OPT,
+            reason,
+        };
+        if record.rtype != RecordType::OPT {
+            return Err(invalid(0, "not an OPT record"));
+        }
+        let RData::Raw(data) = &record.rdata else {
+            return Err(invalid(0, "OPT RDATA is not raw data"));
+        };
+
+        let mut options = Vec::new();
+        let mut offset = 0;
+        while offset < data.len() {
+            let header = data
+                .get(offset..offset + 4)
+                .ok_or(DecodeError::Truncated { offset })?;
+            let code = u16::from_be_bytes([header[0], header[1]]);
+            let len = u16::from_be_bytes([header[2], header[3]]) as usize;
+            let value = data
+                .get(offset + 4..offset + 4 + len)
+                .ok_or(DecodeError::Truncated { offset: offset + 4 })?;
+            options.push(EdnsOption {
+                code,
+                data: value.to_vec(),
+            });
+            offset += 4 + len;
+        }
+
+        Ok(Edns {
+            udp_payload_size: u16::from(record.rclass),
+            ext_rcode: (record.ttl >> 24) as u8,
+            version: (record.ttl >> 16) as u8,
+            flags: record.ttl as u16,
+            options,
+        })
+    }
+
+    pub fn to_record(&self) -> Record {
+        let mut data = Vec::new();
+        for option in &self.options {
+            data.extend_from_slice(&option.code.to_be_bytes());
+            data.extend_from_slice(&(option.data.len() as u16).to_be_bytes());
+            data.extend_from_slice(&option.data);
+        }
+        Record {
+            name: Name::root(),
+            rtype: RecordType::OPT,
+            rclass: RecordClass::from(self.udp_payload_size),
+            ttl: (self.ext_rcode as u32) << 24 | (self.version as u32) << 16 | self.flags as u32,
+            rdata: RData::Raw(data),
+        }
+    }
+}
+
+/// Formats the OPT pseudo-section the way dig does.
+impl fmt::Display for Edns {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "; EDNS: version: {}, flags:", self.version)?;
+        if self.dnssec_ok() {
+            f.write_str(" do")?;
+        }
+        let mbz = self.flags & !Self::DNSSEC_OK;
+        if mbz != 0 {
+            write!(f, "; MBZ: {:#06x}", mbz)?;
+        }
+        write!(f, "; udp: {}", self.udp_payload_size)?;
+        for option in &self.options {
+            write!(f, "\n; {}", option)?;
+        }
+        Ok(())
+    }
+}
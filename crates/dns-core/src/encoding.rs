@@ -0,0 +1,134 @@
+use thiserror::Error;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum EncodingError {
+    #[error("Invalid hex data")]
+    InvalidHex,
+    #[error("Invalid base64 data")]
+    InvalidBase64,
+    #[error("Invalid base32hex data")]
+    InvalidBase32,
+}
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE32HEX_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHIJKLMNOPQRSTUV";
+
+pub fn hex_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len() * 2);
+    for b in data {
+        out.push(HEX_DIGITS[(b >> 4) as usize] as char);
+        out.push(HEX_DIGITS[(b & 0x0F) as usize] as char);
+    }
+    out
+}
+
+pub fn hex_decode(text: &str) -> Result<Vec<u8>, EncodingError> {
+    let digits = text.as_bytes();
+    if !digits.len().is_multiple_of(2) {
+        return Err(EncodingError::InvalidHex);
+    }
+    digits
+        .chunks(2)
+        .map(|pair| {
+            let high = (pair[0] as char).to_digit(16);
+            let low = (pair[1] as char).to_digit(16);
+            match (high, low) {
+                (Some(high), Some(low)) => Ok((high << 4 | low) as u8),
+                _ => Err(EncodingError::InvalidHex),
+            }
+        })
+        .collect()
+}
+
+pub fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+pub fn base64_decode(text: &str) -> Result<Vec<u8>, EncodingError> {
+    let text = text.as_bytes();
+    if !text.len().is_multiple_of(4) {
+        return Err(EncodingError::InvalidBase64);
+    }
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    let chunks = text.len() / 4;
+    for (index, chunk) in text.chunks(4).enumerate() {
+        let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+        if padding > 2 || (padding > 0 && index + 1 != chunks) {
+            return Err(EncodingError::InvalidBase64);
+        }
+        let mut n = 0u32;
+        for &c in &chunk[..4 - padding] {
+            let value = BASE64_ALPHABET
+                .iter()
+                .position(|&a| a == c)
+                .ok_or(EncodingError::InvalidBase64)?;
+            n = n << 6 | value as u32;
+        }
+        n <<= 6 * padding as u32;
+        let bytes = [(n >> 16) as u8, (n >> 8) as u8, n as u8];
+        out.extend_from_slice(&bytes[..3 - padding]);
+    }
+    Ok(out)
+}
+
+/// Encodes with the "Extended Hex" base32 alphabet of RFC 4648 section 7,
+/// without padding, as used for NSEC3 hashed owner names.
+pub fn base32hex_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity((data.len() * 8).div_ceil(5));
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    for &b in data {
+        buffer = buffer << 8 | b as u16;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32HEX_ALPHABET[(buffer >> bits & 0x1F) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32HEX_ALPHABET[(buffer << (5 - bits) & 0x1F) as usize] as char);
+    }
+    out
+}
+
+/// Decodes unpadded, case-insensitive base32hex.
+pub fn base32hex_decode(text: &str) -> Result<Vec<u8>, EncodingError> {
+    let mut out = Vec::with_capacity(text.len() * 5 / 8);
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    for c in text.bytes() {
+        let value = BASE32HEX_ALPHABET
+            .iter()
+            .position(|&a| a == c.to_ascii_uppercase())
+            .ok_or(EncodingError::InvalidBase32)?;
+        buffer = buffer << 5 | value as u16;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    // Leftover bits must be padding zeros from a partial final quantum.
+    if bits >= 5 || buffer & ((1 << bits) - 1) != 0 {
+        return Err(EncodingError::InvalidBase32);
+    }
+    Ok(out)
+}
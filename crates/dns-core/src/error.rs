@@ -0,0 +1,80 @@
+use crate::types::RecordType;
+use std::fmt;
+use std::io;
+use thiserror::Error;
+
+/// The message section a record or question was being read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Section {
+    Question,
+    Answer,
+    Authority,
+    Additional,
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Section::Question => "QUESTION",
+            Section::Answer => "ANSWER",
+            Section::Authority => "AUTHORITY",
+            Section::Additional => "ADDITIONAL",
+        })
+    }
+}
+
+/// Errors produced while decoding wire-format data. Every variant carries
+/// the offset from the start of the message at which decoding failed.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    #[error("Message truncated at offset {offset}")]
+    Truncated { offset: usize },
+    #[error("Invalid compression pointer to {target} at offset {offset}")]
+    BadPointer { offset: usize, target: usize },
+    #[error("Label longer than 63 octets at offset {offset}")]
+    LabelTooLong { offset: usize },
+    #[error("Name longer than 255 octets at offset {offset}")]
+    NameTooLong { offset: usize },
+    #[error("RDATA for {rtype} does not match RDLENGTH at offset {offset}")]
+    RDataLengthMismatch { offset: usize, rtype: RecordType },
+    #[error("Invalid RDATA for {rtype} at offset {offset}: {reason}")]
+    InvalidRData {
+        offset: usize,
+        rtype: RecordType,
+        reason: &'static str,
+    },
+    #[error("{trailing} trailing bytes after message at offset {offset}")]
+    TrailingBytes { offset: usize, trailing: usize },
+    #[error("{section} section declares {expected} entries but only {found} present")]
+    CountMismatch {
+        offset: usize,
+        section: Section,
+        expected: u16,
+        found: u16,
+    },
+}
+
+impl DecodeError {
+    pub fn offset(&self) -> usize {
+        match self {
+            DecodeError::Truncated { offset }
+            | DecodeError::BadPointer { offset, .. }
+            | DecodeError::LabelTooLong { offset }
+            | DecodeError::NameTooLong { offset }
+            | DecodeError::RDataLengthMismatch { offset, .. }
+            | DecodeError::InvalidRData { offset, .. }
+            | DecodeError::TrailingBytes { offset, .. }
+            | DecodeError::CountMismatch { offset, .. } => *offset,
+        }
+    }
+}
+
+impl From<DecodeError> for io::Error {
+    fn from(error: DecodeError) -> Self {
+        let kind = match error {
+            DecodeError::Truncated { .. } => io::ErrorKind::UnexpectedEof,
+            _ => io::ErrorKind::InvalidData,
+        };
+        io::Error::new(kind, error)
+    }
+}
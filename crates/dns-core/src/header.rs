@@ -1,34 +1,148 @@
-use std::io::{self, Read, Write};
+use crate::error::DecodeError;
+use crate::types::{Opcode, Rcode};
+use crate::wire::read_slice;
+use std::fmt;
+use std::io::{self, Cursor, Write};
+use std::ops::{BitOr, BitOrAssign};
 
-#[derive(Debug, Clone)]
+/// The single-bit header flags, laid out as in the second 16-bit word of
+/// the header with the OPCODE and RCODE bits clear.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Flags(u16);
+
+impl Flags {
+    pub const QR: Flags = Flags(0x8000);
+    pub const AA: Flags = Flags(0x0400);
+    pub const TC: Flags = Flags(0x0200);
+    pub const RD: Flags = Flags(0x0100);
+    pub const RA: Flags = Flags(0x0080);
+    /// The reserved bit, which must be zero (RFC 1035).
+    pub const Z: Flags = Flags(0x0040);
+    /// Authentic data (RFC 4035 section 3.2.3).
+    pub const AD: Flags = Flags(0x0020);
+    /// Checking disabled (RFC 4035 section 3.2.2).
+    pub const CD: Flags = Flags(0x0010);
+
+    const ALL: [(Flags, &'static str); 8] = [
+        (Flags::QR, "qr"),
+        (Flags::AA, "aa"),
+        (Flags::TC, "tc"),
+        (Flags::RD, "rd"),
+        (Flags::RA, "ra"),
+        (Flags::Z, "z"),
+        (Flags::AD, "ad"),
+        (Flags::CD, "cd"),
+    ];
+
+    pub fn empty() -> Self {
+        Flags(0)
+    }
+
+    /// Keeps only the flag bits of `bits`, dropping OPCODE and RCODE.
+    pub fn from_bits(bits: u16) -> Self {
+        Flags(bits & 0x87F0)
+    }
+
+    pub fn bits(&self) -> u16 {
+        self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(&self, other: Flags) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn insert(&mut self, other: Flags) {
+        self.0 |= other.0;
+    }
+
+    pub fn remove(&mut self, other: Flags) {
+        self.0 &= !other.0;
+    }
+
+    pub fn set(&mut self, other: Flags, value: bool) {
+        if value {
+            self.insert(other);
+        } else {
+            self.remove(other);
+        }
+    }
+}
+
+impl BitOr for Flags {
+    type Output = Flags;
+
+    fn bitor(self, other: Flags) -> Flags {
+        Flags(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for Flags {
+    fn bitor_assign(&mut self, other: Flags) {
+        self.0 |= other.0;
+    }
+}
+
+/// Lists the set flags in lowercase as dig does, e.g. `qr aa rd ra ad`.
+impl fmt::Display for Flags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for (flag, mnemonic) in Flags::ALL {
+            if self.contains(flag) {
+                if !first {
+                    f.write_str(" ")?;
+                }
+                f.write_str(mnemonic)?;
+                first = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Header {
     pub id: u16,
     pub qr: bool,
-    pub opcode: u8,
+    pub opcode: Opcode,
     pub aa: bool,
     pub tc: bool,
     pub rd: bool,
     pub ra: bool,
-    pub z: u8,
-    pub rcode: u8,
+    pub z: bool,
+    pub ad: bool,
+    pub cd: bool,
+    pub rcode: Rcode,
     pub qdcount: u16,
     pub ancount: u16,
     pub nscount: u16,
     pub arcount: u16,
 }
 
+impl Default for Header {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Header {
     pub fn new() -> Self {
         Header {
             id: 0,
             qr: false,
-            opcode: 0,
+            opcode: Opcode::Query,
             aa: false,
             tc: false,
             rd: false,
             ra: false,
-            z: 0,
-            rcode: 0,
+            z: false,
+            ad: false,
+            cd: false,
+            rcode: Rcode::NoError,
             qdcount: 0,
             ancount: 0,
             nscount: 0,
@@ -36,20 +150,45 @@ impl Header {
         }
     }
 
+    pub fn flags(&self) -> Flags {
+        let mut flags = Flags::empty();
+        flags.set(Flags::QR, self.qr);
+        flags.set(Flags::AA, self.aa);
+        flags.set(Flags::TC, self.tc);
+        flags.set(Flags::RD, self.rd);
+        flags.set(Flags::RA, self.ra);
+        flags.set(Flags::Z, self.z);
+        flags.set(Flags::AD, self.ad);
+        flags.set(Flags::CD, self.cd);
+        flags
+    }
+
+    pub fn set_flags(&mut self, flags: Flags) {
+        self.qr = flags.contains(Flags::QR);
+        self.aa = flags.contains(Flags::AA);
+        self.tc = flags.contains(Flags::TC);
+        self.rd = flags.contains(Flags::RD);
+        self.ra = flags.contains(Flags::RA);
+        self.z = flags.contains(Flags::Z);
+        self.ad = flags.contains(Flags::AD);
+        self.cd = flags.contains(Flags::CD);
+    }
+
     // Methods to parse and write headers
-    pub fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
-        let mut buf = [0u8; 12];
-        reader.read_exact(&mut buf)?;
+    pub fn read(reader: &mut Cursor<&[u8]>) -> Result<Self, DecodeError> {
+        let buf = read_slice(reader, 12)?;
         Ok(Header {
             id: u16::from_be_bytes([buf[0], buf[1]]),
             qr: (buf[2] & 0x80) != 0,
-            opcode: (buf[2] & 0x78) >> 3,
+            opcode: Opcode::from((buf[2] & 0x78) >> 3),
             aa: (buf[2] & 0x04) != 0,
             tc: (buf[2] & 0x02) != 0,
             rd: (buf[2] & 0x01) != 0,
             ra: (buf[3] & 0x80) != 0,
-            z: (buf[3] & 0x70) >> 4,
-            rcode: buf[3] & 0x0F,
+            z: (buf[3] & 0x40) != 0,
+            ad: (buf[3] & 0x20) != 0,
+            cd: (buf[3] & 0x10) != 0,
+            rcode: Rcode::from((buf[3] & 0x0F) as u16),
             qdcount: u16::from_be_bytes([buf[4], buf[5]]),
             ancount: u16::from_be_bytes([buf[6], buf[7]]),
             nscount: u16::from_be_bytes([buf[8], buf[9]]),
@@ -58,10 +197,17 @@ impl Header {
     }
 
     pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let opcode = u8::from(self.opcode);
+        if opcode > 0x0F {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Opcode does not fit in four bits",
+            ));
+        }
         writer.write_all(&self.id.to_be_bytes())?;
         let mut buf2 = 0u8;
         buf2 |= (self.qr as u8) << 7;
-        buf2 |= (self.opcode & 0x0F) << 3;
+        buf2 |= opcode << 3;
         buf2 |= (self.aa as u8) << 2;
         buf2 |= (self.tc as u8) << 1;
         buf2 |= self.rd as u8;
@@ -69,8 +215,10 @@ impl Header {
 
         let mut buf3 = 0u8;
         buf3 |= (self.ra as u8) << 7;
-        buf3 |= (self.z & 0x07) << 4;
-        buf3 |= self.rcode & 0x0F;
+        buf3 |= (self.z as u8) << 6;
+        buf3 |= (self.ad as u8) << 5;
+        buf3 |= (self.cd as u8) << 4;
+        buf3 |= self.rcode.header_bits();
         writer.write_all(&[buf3])?;
 
         writer.write_all(&self.qdcount.to_be_bytes())?;
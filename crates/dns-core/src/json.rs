@@ -0,0 +1,304 @@
+//! The "DNS in JSON" representation of RFC 8427.
+
+use crate::encoding::{hex_decode, hex_encode, EncodingError};
+use crate::error::DecodeError;
+use crate::header::Header;
+use crate::message::Message;
+use crate::name::Name;
+use crate::question::Question;
+use crate::record::{RData, Record};
+use crate::text::{self, ParseError};
+use crate::types::{Opcode, Rcode, RecordClass, RecordType};
+use serde_json::{Map, Value};
+use std::io::{self, Cursor};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum JsonError {
+    #[error("Missing member {0}")]
+    MissingMember(String),
+    #[error("Invalid value for member {0}")]
+    InvalidMember(String),
+    #[error("Invalid hex: {0}")]
+    Encoding(#[from] EncodingError),
+    #[error("Invalid message octets: {0}")]
+    Decode(#[from] DecodeError),
+    #[error("Invalid RDATA: {0}")]
+    Parse(#[from] ParseError),
+}
+
+impl Message {
+    /// Encodes the message as an RFC 8427 object. A single question is
+    /// given as `QNAME`, `QTYPE` and `QCLASS` members, and RDATA in its
+    /// presentation format under `rdata<TYPE>`, or as `RDATAHEX` for data
+    /// of unknown types.
+    pub fn to_json(&self) -> Value {
+        let header = &self.header;
+        let mut object = Map::new();
+        object.insert("ID".into(), header.id.into());
+        object.insert("QR".into(), header.qr.into());
+        object.insert("Opcode".into(), u8::from(header.opcode).into());
+        object.insert("AA".into(), header.aa.into());
+        object.insert("TC".into(), header.tc.into());
+        object.insert("RD".into(), header.rd.into());
+        object.insert("RA".into(), header.ra.into());
+        object.insert("AD".into(), header.ad.into());
+        object.insert("CD".into(), header.cd.into());
+        object.insert("RCODE".into(), header.rcode.header_bits().into());
+        object.insert("QDCOUNT".into(), self.questions.len().into());
+        object.insert("ANCOUNT".into(), self.answers.len().into());
+        object.insert("NSCOUNT".into(), self.authorities.len().into());
+        object.insert("ARCOUNT".into(), self.additionals.len().into());
+
+        if let [question] = self.questions.as_slice() {
+            object.insert("QNAME".into(), question.qname.to_string().into());
+            insert_type_and_class(&mut object, "Q", question.qtype, question.qclass);
+        } else if !self.questions.is_empty() {
+            let questions = self
+                .questions
+                .iter()
+                .map(|question| {
+                    let mut object = Map::new();
+                    object.insert("NAME".into(), question.qname.to_string().into());
+                    insert_type_and_class(&mut object, "", question.qtype, question.qclass);
+                    Value::Object(object)
+                })
+                .collect();
+            object.insert("questionRRs".into(), Value::Array(questions));
+        }
+
+        for (member, records) in [
+            ("answerRRs", &self.answers),
+            ("authorityRRs", &self.authorities),
+            ("additionalRRs", &self.additionals),
+        ] {
+            if !records.is_empty() {
+                let records = records.iter().map(record_to_json).collect();
+                object.insert(member.into(), Value::Array(records));
+            }
+        }
+        Value::Object(object)
+    }
+
+    /// Like [`Message::to_json`], adding the wire form of the whole message
+    /// as `messageOctetsHEX`.
+    pub fn to_json_with_octets(&self) -> io::Result<Value> {
+        let mut value = self.to_json();
+        if let Value::Object(object) = &mut value {
+            object.insert(
+                "messageOctetsHEX".into(),
+                hex_encode(&self.to_bytes()?).into(),
+            );
+        }
+        Ok(value)
+    }
+
+    /// Decodes an RFC 8427 object. When `messageOctetsHEX` is present the
+    /// message is decoded from it and the other members are ignored.
+    pub fn from_json(value: &Value) -> Result<Message, JsonError> {
+        let object = value
+            .as_object()
+            .ok_or_else(|| JsonError::InvalidMember("message".to_string()))?;
+        if let Some(hex) = object.get("messageOctetsHEX") {
+            let hex = hex
+                .as_str()
+                .ok_or_else(|| JsonError::InvalidMember("messageOctetsHEX".to_string()))?;
+            return Ok(Message::from_bytes(&hex_decode(hex)?)?);
+        }
+
+        let mut header = Header::new();
+        header.id = integer(object, "ID")?.unwrap_or(0);
+        header.qr = flag(object, "QR")?;
+        header.opcode = Opcode::from(integer::<u8>(object, "Opcode")?.unwrap_or(0));
+        header.aa = flag(object, "AA")?;
+        header.tc = flag(object, "TC")?;
+        header.rd = flag(object, "RD")?;
+        header.ra = flag(object, "RA")?;
+        header.ad = flag(object, "AD")?;
+        header.cd = flag(object, "CD")?;
+        header.rcode = Rcode::from(integer::<u16>(object, "RCODE")?.unwrap_or(0) & 0x0F);
+
+        let mut message = Message {
+            header,
+            ..Message::new()
+        };
+        if let Some(qname) = object.get("QNAME") {
+            message.questions.push(Question {
+                qname: name_from_json(qname, "QNAME")?,
+                qtype: type_from_json(object, "Q")?,
+                qclass: class_from_json(object, "Q")?,
+            });
+        }
+        for question in array(object, "questionRRs")? {
+            let question = question
+                .as_object()
+                .ok_or_else(|| JsonError::InvalidMember("questionRRs".to_string()))?;
+            message.questions.push(Question {
+                qname: name_from_json(member(question, "NAME")?, "NAME")?,
+                qtype: type_from_json(question, "")?,
+                qclass: class_from_json(question, "")?,
+            });
+        }
+        for (name, section) in [
+            ("answerRRs", &mut message.answers),
+            ("authorityRRs", &mut message.authorities),
+            ("additionalRRs", &mut message.additionals),
+        ] {
+            for record in array(object, name)? {
+                section.push(record_from_json(record)?);
+            }
+        }
+        message.update_counts();
+        Ok(message)
+    }
+}
+
+fn record_to_json(record: &Record) -> Value {
+    let mut object = Map::new();
+    object.insert("NAME".into(), record.name.to_string().into());
+    insert_type_and_class(&mut object, "", record.rtype, record.rclass);
+    object.insert("TTL".into(), record.ttl.into());
+    match &record.rdata {
+        RData::Raw(data) => {
+            object.insert("RDLENGTH".into(), data.len().into());
+            object.insert("RDATAHEX".into(), hex_encode(data).into());
+        }
+        rdata => {
+            object.insert(format!("rdata{}", record.rtype), rdata.to_string().into());
+        }
+    }
+    Value::Object(object)
+}
+
+fn record_from_json(value: &Value) -> Result<Record, JsonError> {
+    let object = value
+        .as_object()
+        .ok_or_else(|| JsonError::InvalidMember("RR".to_string()))?;
+    let rtype = type_from_json(object, "")?;
+    let rdata = if let Some(hex) = object.get("RDATAHEX") {
+        let hex = hex
+            .as_str()
+            .ok_or_else(|| JsonError::InvalidMember("RDATAHEX".to_string()))?;
+        let data = hex_decode(hex)?;
+        let length = u16::try_from(data.len())
+            .map_err(|_| JsonError::InvalidMember("RDATAHEX".to_string()))?;
+        let mut reader = Cursor::new(&data[..]);
+        let rdata = RData::read(&mut reader, rtype, length)?;
+        if reader.position() != length as u64 {
+            return Err(JsonError::InvalidMember("RDATAHEX".to_string()));
+        }
+        rdata
+    } else {
+        let name = format!("rdata{}", rtype);
+        let text = member(object, &name)?
+            .as_str()
+            .ok_or_else(|| JsonError::InvalidMember(name.clone()))?;
+        text::parse_rdata(rtype, &text::tokenize(text)?, None)?
+    };
+    Ok(Record {
+        name: name_from_json(member(object, "NAME")?, "NAME")?,
+        rtype,
+        rclass: class_from_json(object, "")?,
+        ttl: integer(object, "TTL")?.unwrap_or(0),
+        rdata,
+    })
+}
+
+/// Adds `<prefix>TYPE` and `<prefix>CLASS` with their `name` companions
+/// when the values have mnemonics.
+fn insert_type_and_class(
+    object: &mut Map<String, Value>,
+    prefix: &str,
+    rtype: RecordType,
+    rclass: RecordClass,
+) {
+    object.insert(format!("{}TYPE", prefix), u16::from(rtype).into());
+    if let Some(mnemonic) = rtype.mnemonic() {
+        object.insert(format!("{}TYPEname", prefix), mnemonic.into());
+    }
+    object.insert(format!("{}CLASS", prefix), u16::from(rclass).into());
+    if let Some(mnemonic) = rclass.mnemonic() {
+        object.insert(format!("{}CLASSname", prefix), mnemonic.into());
+    }
+}
+
+fn type_from_json(object: &Map<String, Value>, prefix: &str) -> Result<RecordType, JsonError> {
+    let name = format!("{}TYPE", prefix);
+    if let Some(value) = integer::<u16>(object, &name)? {
+        return Ok(RecordType::from(value));
+    }
+    let name = format!("{}TYPEname", prefix);
+    member(object, &name)?
+        .as_str()
+        .and_then(|text| text.parse().ok())
+        .ok_or(JsonError::InvalidMember(name))
+}
+
+/// The class, IN when neither `<prefix>CLASS` nor `<prefix>CLASSname` is
+/// given.
+fn class_from_json(object: &Map<String, Value>, prefix: &str) -> Result<RecordClass, JsonError> {
+    let name = format!("{}CLASS", prefix);
+    if let Some(value) = integer::<u16>(object, &name)? {
+        return Ok(RecordClass::from(value));
+    }
+    let name = format!("{}CLASSname", prefix);
+    match object.get(&name) {
+        Some(value) => value
+            .as_str()
+            .and_then(|text| text.parse().ok())
+            .ok_or(JsonError::InvalidMember(name)),
+        None => Ok(RecordClass::IN),
+    }
+}
+
+fn name_from_json(value: &Value, member: &str) -> Result<Name, JsonError> {
+    let text = value
+        .as_str()
+        .ok_or_else(|| JsonError::InvalidMember(member.to_string()))?;
+    Name::parse(text, None).map_err(|e| ParseError::Name(e).into())
+}
+
+fn member<'a>(object: &'a Map<String, Value>, name: &str) -> Result<&'a Value, JsonError> {
+    object
+        .get(name)
+        .ok_or_else(|| JsonError::MissingMember(name.to_string()))
+}
+
+fn integer<T: TryFrom<u64>>(
+    object: &Map<String, Value>,
+    name: &str,
+) -> Result<Option<T>, JsonError> {
+    object
+        .get(name)
+        .map(|value| {
+            value
+                .as_u64()
+                .and_then(|n| T::try_from(n).ok())
+                .ok_or_else(|| JsonError::InvalidMember(name.to_string()))
+        })
+        .transpose()
+}
+
+/// A boolean header flag. RFC 8427 specifies booleans, but 0 and 1 are
+/// accepted as well.
+fn flag(object: &Map<String, Value>, name: &str) -> Result<bool, JsonError> {
+    match object.get(name) {
+        None => Ok(false),
+        Some(Value::Bool(value)) => Ok(*value),
+        Some(value) => match value.as_u64() {
+            Some(0) => Ok(false),
+            Some(1) => Ok(true),
+            _ => Err(JsonError::InvalidMember(name.to_string())),
+        },
+    }
+}
+
+fn array<'a>(object: &'a Map<String, Value>, name: &str) -> Result<&'a [Value], JsonError> {
+    match object.get(name) {
+        None => Ok(&[]),
+        Some(value) => value
+            .as_array()
+            .map(Vec::as_slice)
+            .ok_or_else(|| JsonError::InvalidMember(name.to_string())),
+    }
+}
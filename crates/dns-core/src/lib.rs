@@ -1,11 +1,43 @@
+pub mod builder;
 pub mod compression;
+pub mod dnssec;
+pub mod edns;
+pub mod encoding;
+pub mod error;
 pub mod header;
+#[cfg(feature = "serde")]
+pub mod json;
+pub mod loc;
 pub mod message;
+pub mod message_ref;
+pub mod name;
 pub mod question;
 pub mod record;
+pub mod rrset;
+pub mod security;
+#[cfg(feature = "serde")]
+mod serde_str;
+pub mod svcb;
+pub mod text;
+pub mod types;
+mod wire;
 
+pub use builder::MessageBuilder;
 pub use compression::{compress_name, decompress_name};
-pub use header::Header;
+pub use dnssec::{Csync, Dnskey, Ds, Nsec, Nsec3, Nsec3Param, Rrsig, TypeBitmap, Zonemd};
+pub use edns::{Edns, EdnsOption};
+pub use error::{DecodeError, Section};
+pub use header::{Flags, Header};
+#[cfg(feature = "serde")]
+pub use json::JsonError;
+pub use loc::{Loc, LocError};
 pub use message::Message;
+pub use message_ref::{MessageRef, NameRef, QuestionRef, RecordRef};
+pub use name::{Name, NameError};
 pub use question::Question;
 pub use record::{RData, Record};
+pub use rrset::RRset;
+pub use security::{Caa, Cert, Sshfp, Tlsa};
+pub use svcb::{SvcParam, SvcParamKey, Svcb, SvcbError};
+pub use text::ParseError;
+pub use types::{Opcode, Rcode, RecordClass, RecordType};
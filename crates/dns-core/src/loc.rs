@@ -0,0 +1,276 @@
+use crate::error::DecodeError;
+use crate::types::RecordType;
+use crate::wire::{read_u32, read_u8};
+use std::fmt;
+use std::io::{self, Cursor, Write};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Latitude and longitude are offsets from the equator and prime meridian
+/// in thousandths of an arc second, biased by 2^31.
+const ANGLE_ORIGIN: i64 = 1 << 31;
+/// Altitude is in centimetres above a base 100,000 m below the WGS 84
+/// reference spheroid.
+const ALTITUDE_ORIGIN: i64 = 10_000_000;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("Invalid LOC data: {0}")]
+pub struct LocError(pub String);
+
+/// LOC RDATA (RFC 1876), kept in its wire encoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Loc {
+    pub version: u8,
+    pub size: u8,
+    pub horiz_pre: u8,
+    pub vert_pre: u8,
+    pub latitude: u32,
+    pub longitude: u32,
+    pub altitude: u32,
+}
+
+impl Loc {
+    /// Latitude in thousandths of an arc second, positive to the north.
+    pub fn latitude_millis(&self) -> i64 {
+        self.latitude as i64 - ANGLE_ORIGIN
+    }
+
+    /// Longitude in thousandths of an arc second, positive to the east.
+    pub fn longitude_millis(&self) -> i64 {
+        self.longitude as i64 - ANGLE_ORIGIN
+    }
+
+    /// Altitude in centimetres relative to the WGS 84 spheroid.
+    pub fn altitude_cm(&self) -> i64 {
+        self.altitude as i64 - ALTITUDE_ORIGIN
+    }
+
+    pub fn size_cm(&self) -> u64 {
+        decode_precision(self.size)
+    }
+
+    pub fn horiz_pre_cm(&self) -> u64 {
+        decode_precision(self.horiz_pre)
+    }
+
+    pub fn vert_pre_cm(&self) -> u64 {
+        decode_precision(self.vert_pre)
+    }
+
+    pub(crate) fn read(reader: &mut Cursor<&[u8]>) -> Result<Self, DecodeError> {
+        let offset = reader.position() as usize;
+        let loc = Loc {
+            version: read_u8(reader)?,
+            size: read_u8(reader)?,
+            horiz_pre: read_u8(reader)?,
+            vert_pre: read_u8(reader)?,
+            latitude: read_u32(reader)?,
+            longitude: read_u32(reader)?,
+            altitude: read_u32(reader)?,
+        };
+        let invalid = |reason| DecodeError::InvalidRData {
+            offset,
+            rtype: RecordType::LOC,
+            reason,
+        };
+        if loc.version != 0 {
+            return Err(invalid("unsupported LOC version"));
+        }
+        if [loc.size, loc.horiz_pre, loc.vert_pre]
+            .iter()
+            .any(|&p| p >> 4 > 9 || p & 0x0F > 9)
+        {
+            return Err(invalid("invalid LOC precision"));
+        }
+        Ok(loc)
+    }
+
+    pub(crate) fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&[self.version, self.size, self.horiz_pre, self.vert_pre])?;
+        writer.write_all(&self.latitude.to_be_bytes())?;
+        writer.write_all(&self.longitude.to_be_bytes())?;
+        writer.write_all(&self.altitude.to_be_bytes())
+    }
+}
+
+/// Parses the presentation format of RFC 1876 section 3:
+/// `d1 [m1 [s1]] N|S d2 [m2 [s2]] E|W alt[m] [siz[m] [hp[m] [vp[m]]]]`.
+impl FromStr for Loc {
+    type Err = LocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.split_whitespace().peekable();
+        let latitude = parse_angle(&mut fields, ('N', 'S'), 90)?;
+        let longitude = parse_angle(&mut fields, ('E', 'W'), 180)?;
+
+        let altitude = fields
+            .next()
+            .and_then(parse_centimetres)
+            .filter(|cm| (-ALTITUDE_ORIGIN..=u32::MAX as i64 - ALTITUDE_ORIGIN).contains(cm))
+            .ok_or_else(|| LocError(format!("invalid altitude in {}", s)))?;
+
+        // Defaults from RFC 1876: 1 m size, 10 km horizontal and 10 m
+        // vertical precision.
+        let mut precisions = [100, 1_000_000, 1_000];
+        for precision in precisions.iter_mut() {
+            let Some(field) = fields.next() else { break };
+            *precision = parse_centimetres(field)
+                .filter(|cm| (0..=9_000_000_000).contains(cm))
+                .ok_or_else(|| LocError(format!("invalid precision {}", field)))?;
+        }
+        if let Some(field) = fields.next() {
+            return Err(LocError(format!("unexpected data {}", field)));
+        }
+
+        Ok(Loc {
+            version: 0,
+            size: encode_precision(precisions[0] as u64),
+            horiz_pre: encode_precision(precisions[1] as u64),
+            vert_pre: encode_precision(precisions[2] as u64),
+            latitude: (ANGLE_ORIGIN + latitude) as u32,
+            longitude: (ANGLE_ORIGIN + longitude) as u32,
+            altitude: (altitude + ALTITUDE_ORIGIN) as u32,
+        })
+    }
+}
+
+impl fmt::Display for Loc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_angle(f, self.latitude_millis(), ('N', 'S'))?;
+        f.write_str(" ")?;
+        fmt_angle(f, self.longitude_millis(), ('E', 'W'))?;
+        f.write_str(" ")?;
+        fmt_metres(f, self.altitude_cm())?;
+        for cm in [self.size_cm(), self.horiz_pre_cm(), self.vert_pre_cm()] {
+            f.write_str(" ")?;
+            fmt_metres(f, cm as i64)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses degrees, optional minutes and seconds, and a hemisphere letter
+/// into signed thousandths of an arc second.
+fn parse_angle<'a, I: Iterator<Item = &'a str>>(
+    fields: &mut std::iter::Peekable<I>,
+    (positive, negative): (char, char),
+    max_degrees: i64,
+) -> Result<i64, LocError> {
+    let invalid = |what: &str| LocError(format!("invalid {}", what));
+    let is_hemisphere = |field: &&str| {
+        field.eq_ignore_ascii_case(&positive.to_string())
+            || field.eq_ignore_ascii_case(&negative.to_string())
+    };
+
+    let degrees: i64 = fields
+        .next()
+        .and_then(|d| d.parse().ok())
+        .ok_or_else(|| invalid("degrees"))?;
+    let mut minutes = 0;
+    let mut millis = 0;
+    if fields.peek().is_some_and(|f| !is_hemisphere(f)) {
+        minutes = fields
+            .next()
+            .and_then(|m| m.parse().ok())
+            .filter(|m| (0..60).contains(m))
+            .ok_or_else(|| invalid("minutes"))?;
+        if fields.peek().is_some_and(|f| !is_hemisphere(f)) {
+            millis = fields
+                .next()
+                .and_then(|s| parse_fixed(s, 3))
+                .filter(|s| (0..60_000).contains(s))
+                .ok_or_else(|| invalid("seconds"))?;
+        }
+    }
+    let hemisphere = fields
+        .next()
+        .filter(is_hemisphere)
+        .ok_or_else(|| invalid("hemisphere"))?;
+
+    let value = ((degrees * 60 + minutes) * 60) * 1000 + millis;
+    if !(0..=max_degrees).contains(&degrees) || value > max_degrees * 3_600_000 {
+        return Err(invalid("angle"));
+    }
+    if hemisphere.eq_ignore_ascii_case(&negative.to_string()) {
+        Ok(-value)
+    } else {
+        Ok(value)
+    }
+}
+
+fn fmt_angle(
+    f: &mut fmt::Formatter<'_>,
+    millis: i64,
+    (positive, negative): (char, char),
+) -> fmt::Result {
+    let hemisphere = if millis < 0 { negative } else { positive };
+    let millis = millis.abs();
+    write!(
+        f,
+        "{} {} {}.{:03} {}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000,
+        hemisphere
+    )
+}
+
+/// Parses a distance in metres with up to two decimals and an optional `m`
+/// suffix into centimetres.
+fn parse_centimetres(field: &str) -> Option<i64> {
+    let field = field.strip_suffix(['m', 'M']).unwrap_or(field);
+    parse_fixed(field, 2)
+}
+
+fn fmt_metres(f: &mut fmt::Formatter<'_>, cm: i64) -> fmt::Result {
+    let sign = if cm < 0 { "-" } else { "" };
+    let cm = cm.abs();
+    if cm % 100 == 0 {
+        write!(f, "{}{}m", sign, cm / 100)
+    } else {
+        write!(f, "{}{}.{:02}m", sign, cm / 100, cm % 100)
+    }
+}
+
+/// Parses a decimal with at most `scale` fractional digits as an integer
+/// scaled by 10^scale.
+fn parse_fixed(field: &str, scale: u32) -> Option<i64> {
+    let (negative, field) = match field.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, field),
+    };
+    let (whole, fraction) = field.split_once('.').unwrap_or((field, ""));
+    if whole.is_empty()
+        || fraction.len() > scale as usize
+        || !whole
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let whole: i64 = whole.parse().ok()?;
+    let fraction: i64 = format!("{:0<width$}", fraction, width = scale as usize)
+        .parse()
+        .ok()?;
+    let value = whole.checked_mul(10i64.pow(scale))? + fraction;
+    Some(if negative { -value } else { value })
+}
+
+/// Encodes centimetres as the mantissa/exponent pair of RFC 1876, rounding
+/// down to one significant digit.
+fn encode_precision(cm: u64) -> u8 {
+    let mut mantissa = cm;
+    let mut exponent = 0;
+    while mantissa >= 10 && exponent < 9 {
+        mantissa /= 10;
+        exponent += 1;
+    }
+    (mantissa.min(9) as u8) << 4 | exponent
+}
+
+fn decode_precision(value: u8) -> u64 {
+    (value >> 4) as u64 * 10u64.pow((value & 0x0F) as u32)
+}
@@ -1,7 +1,14 @@
-use crate::{Header, Question, Record};
-use std::io::{self, Read, Write};
+use crate::edns::Edns;
+use crate::error::DecodeError;
+use crate::message_ref::MessageRef;
+use crate::types::{Rcode, RecordClass, RecordType};
+use crate::{Header, Name, Question, RData, Record};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Cursor, Read, Write};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Message {
     pub header: Header,
     pub questions: Vec<Question>,
@@ -10,6 +17,12 @@ pub struct Message {
     pub additionals: Vec<Record>,
 }
 
+impl Default for Message {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Message {
     pub fn new() -> Self {
         Message {
@@ -21,46 +34,211 @@ impl Message {
         }
     }
 
-    pub fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
-        let header = Header::read(reader)?;
-        let mut questions = Vec::with_capacity(header.qdcount as usize);
-        for _ in 0..header.qdcount {
-            questions.push(Question::read(reader)?);
-        }
-        let mut answers = Vec::with_capacity(header.ancount as usize);
-        for _ in 0..header.ancount {
-            answers.push(Record::read(reader).unwrap());
+    /// The full response code, combining the header RCODE with the extended
+    /// bits from the OPT record when one is present.
+    pub fn rcode(&self) -> Rcode {
+        let ext_rcode = self.opt().map_or(0, |opt| (opt.ttl >> 24) as u8);
+        Rcode::from_parts(self.header.rcode.header_bits(), ext_rcode)
+    }
+
+    /// Sets the response code, storing the extended bits in the OPT record.
+    /// Extended codes are silently truncated to 4 bits without one.
+    pub fn set_rcode(&mut self, rcode: Rcode) {
+        self.header.rcode = Rcode::from(rcode.header_bits() as u16);
+        if let Some(opt) = self
+            .additionals
+            .iter_mut()
+            .find(|r| r.rtype == RecordType::OPT)
+        {
+            opt.ttl = (opt.ttl & 0x00FF_FFFF) | ((rcode.ext_bits() as u32) << 24);
         }
-        let mut authorities = Vec::with_capacity(header.nscount as usize);
-        for _ in 0..header.nscount {
-            authorities.push(Record::read(reader).unwrap());
+    }
+
+    /// Sets the header counts to the number of entries in each section,
+    /// saturating at `u16::MAX`.
+    pub fn update_counts(&mut self) {
+        let count = |len: usize| u16::try_from(len).unwrap_or(u16::MAX);
+        self.header.qdcount = count(self.questions.len());
+        self.header.ancount = count(self.answers.len());
+        self.header.nscount = count(self.authorities.len());
+        self.header.arcount = count(self.additionals.len());
+    }
+
+    pub fn opt(&self) -> Option<&Record> {
+        self.additionals.iter().find(|r| r.rtype == RecordType::OPT)
+    }
+
+    /// The EDNS parameters of the OPT record, if there is a well-formed one.
+    pub fn edns(&self) -> Option<Edns> {
+        self.opt().and_then(|opt| Edns::from_record(opt).ok())
+    }
+
+    /// Reads a whole message from `reader`. Compression pointers are
+    /// resolved against the complete message, so the reader must yield
+    /// exactly one message.
+    pub fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf)?;
+        Ok(Self::from_bytes(&buf)?)
+    }
+
+    pub fn from_bytes(buf: &[u8]) -> Result<Self, DecodeError> {
+        MessageRef::new(buf)?.to_message()
+    }
+
+    /// Writes the message with name compression applied to owner names and
+    /// to the names embedded in well-known RDATA.
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.to_bytes()?)
+    }
+
+    /// Encodes the message in at most `max` bytes. Additional and then
+    /// authority RRsets are dropped from the end of their sections until the
+    /// message fits, keeping the OPT record. If the answers still do not fit
+    /// they are dropped too and TC is set (RFC 2181 section 9).
+    pub fn to_wire_limited(&self, max: usize) -> io::Result<Vec<u8>> {
+        let bytes = self.to_bytes()?;
+        if bytes.len() <= max {
+            return Ok(bytes);
         }
-        let mut additionals = Vec::with_capacity(header.arcount as usize);
-        for _ in 0..header.arcount {
-            additionals.push(Record::read(reader).unwrap());
+
+        let mut message = self.clone();
+        let (opt, additionals) = message
+            .additionals
+            .drain(..)
+            .partition(|r| r.rtype == RecordType::OPT);
+        message.additionals = additionals;
+        loop {
+            let mut candidate = message.clone();
+            candidate.additionals.extend(opt.iter().cloned());
+            let bytes = candidate.to_bytes()?;
+            if bytes.len() <= max {
+                return Ok(bytes);
+            }
+            if drop_last_rrset(&mut message.additionals)
+                || drop_last_rrset(&mut message.authorities)
+            {
+                continue;
+            }
+            if !drop_last_rrset(&mut message.answers) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Message does not fit in the size limit",
+                ));
+            }
+            message.header.tc = true;
         }
-        Ok(Message {
-            header,
-            questions,
-            answers,
-            authorities,
-            additionals,
-        })
     }
 
-    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
-        self.header.write(writer)?;
+    /// The largest UDP response the sender of this request accepts: its EDNS
+    /// payload size, or 512 bytes without EDNS (RFC 6891 section 6.2.5).
+    pub fn udp_payload_size(&self) -> usize {
+        self.edns()
+            .map_or(512, |edns| edns.udp_payload_size.max(512) as usize)
+    }
+
+    /// Encodes the message. The header counts are taken from the sections,
+    /// whatever the values in `header`.
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        let mut buf = Cursor::new(Vec::with_capacity(512));
+        let mut compression_map = HashMap::new();
+        let header = Header {
+            qdcount: section_count(self.questions.len())?,
+            ancount: section_count(self.answers.len())?,
+            nscount: section_count(self.authorities.len())?,
+            arcount: section_count(self.additionals.len())?,
+            ..self.header.clone()
+        };
+        header.write(&mut buf)?;
         for question in &self.questions {
-            question.write(writer)?;
+            question.write_compressed(&mut buf, &mut compression_map)?;
         }
         for answer in &self.answers {
-            answer.write(writer)?;
+            answer.write_compressed(&mut buf, &mut compression_map)?;
         }
         for authority in &self.authorities {
-            authority.write(writer)?;
+            authority.write_compressed(&mut buf, &mut compression_map)?;
         }
         for additional in &self.additionals {
-            additional.write(writer)?;
+            additional.write_compressed(&mut buf, &mut compression_map)?;
+        }
+        Ok(buf.into_inner())
+    }
+}
+
+/// Removes the RRset of the last record in `records`, wherever its members
+/// are. Signatures count as part of the RRset they cover.
+fn drop_last_rrset(records: &mut Vec<Record>) -> bool {
+    let Some(last) = records.pop() else {
+        return false;
+    };
+    let key = rrset_key(&last);
+    records.retain(|r| rrset_key(r) != key);
+    true
+}
+
+fn rrset_key(record: &Record) -> (&Name, RecordClass, RecordType) {
+    let rtype = match &record.rdata {
+        RData::RRSIG(rrsig) => rrsig.type_covered,
+        _ => record.rtype,
+    };
+    (&record.name, record.rclass, rtype)
+}
+
+fn section_count(len: usize) -> io::Result<u16> {
+    u16::try_from(len)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Too many records in section"))
+}
+
+/// Formats the message like the output of dig: a header comment, the OPT
+/// pseudo-section and each non-empty section. Counts are taken from the
+/// sections rather than the header fields.
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let header = &self.header;
+        writeln!(
+            f,
+            ";; ->>HEADER<<- opcode: {}, status: {}, id: {}",
+            header.opcode,
+            self.rcode(),
+            header.id
+        )?;
+        write!(f, ";; flags: {}", header.flags())?;
+        writeln!(
+            f,
+            "; QUERY: {}, ANSWER: {}, AUTHORITY: {}, ADDITIONAL: {}",
+            self.questions.len(),
+            self.answers.len(),
+            self.authorities.len(),
+            self.additionals.len()
+        )?;
+
+        if let Some(edns) = self.edns() {
+            write!(f, "\n;; OPT PSEUDOSECTION:\n{}\n", edns)?;
+        }
+        if !self.questions.is_empty() {
+            f.write_str("\n;; QUESTION SECTION:\n")?;
+            for question in &self.questions {
+                writeln!(f, ";{}", question)?;
+            }
+        }
+        let additionals: Vec<&Record> = self
+            .additionals
+            .iter()
+            .filter(|r| r.rtype != RecordType::OPT)
+            .collect();
+        for (section, records) in [
+            ("ANSWER", self.answers.iter().collect()),
+            ("AUTHORITY", self.authorities.iter().collect()),
+            ("ADDITIONAL", additionals),
+        ] {
+            if records.is_empty() {
+                continue;
+            }
+            write!(f, "\n;; {} SECTION:\n", section)?;
+            for record in records {
+                writeln!(f, "{}", record)?;
+            }
         }
         Ok(())
     }
@@ -0,0 +1,484 @@
+use crate::error::{DecodeError, Section};
+use crate::name::{write_label, Name, MAX_NAME_LEN};
+use crate::types::{RecordClass, RecordType};
+use crate::wire::{u16_at, u32_at};
+use crate::{Header, Message, Question, RData, Record};
+use std::fmt;
+use std::io::Cursor;
+
+/// A DNS name is at most 255 octets, so no legitimate name needs more
+/// pointer jumps than that.
+const MAX_POINTER_HOPS: usize = 128;
+const HEADER_LEN: usize = 12;
+
+/// A borrowed view over a wire-format message.
+///
+/// Only the header is decoded up front; questions and records are parsed
+/// lazily by the section iterators, and names are read in place without
+/// allocating.
+#[derive(Debug, Clone)]
+pub struct MessageRef<'a> {
+    buf: &'a [u8],
+    header: Header,
+}
+
+impl<'a> MessageRef<'a> {
+    pub fn new(buf: &'a [u8]) -> Result<Self, DecodeError> {
+        let header = Header::read(&mut Cursor::new(buf))?;
+        Ok(MessageRef { buf, header })
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.buf
+    }
+
+    pub fn questions(&self) -> SectionIter<'a, QuestionRef<'a>> {
+        SectionIter::new(
+            self.buf,
+            Ok(HEADER_LEN),
+            Section::Question,
+            self.header.qdcount,
+            QuestionRef::parse,
+        )
+    }
+
+    pub fn answers(&self) -> SectionIter<'a, RecordRef<'a>> {
+        self.records(Section::Answer)
+    }
+
+    pub fn authorities(&self) -> SectionIter<'a, RecordRef<'a>> {
+        self.records(Section::Authority)
+    }
+
+    pub fn additionals(&self) -> SectionIter<'a, RecordRef<'a>> {
+        self.records(Section::Additional)
+    }
+
+    /// Decodes the whole message into its owned form.
+    pub fn to_message(&self) -> Result<Message, DecodeError> {
+        let mut questions = Vec::new();
+        let mut iter = self.questions();
+        for question in iter.by_ref() {
+            questions.push(question?.to_question());
+        }
+        let mut sections = [Vec::new(), Vec::new(), Vec::new()];
+        let mut offset = iter.offset();
+        for (records, section) in
+            sections
+                .iter_mut()
+                .zip([Section::Answer, Section::Authority, Section::Additional])
+        {
+            let mut iter = self.records_at(section, Ok(offset));
+            for record in iter.by_ref() {
+                records.push(record?.to_record()?);
+            }
+            offset = iter.offset();
+        }
+        if offset < self.buf.len() {
+            return Err(DecodeError::TrailingBytes {
+                offset,
+                trailing: self.buf.len() - offset,
+            });
+        }
+        let [answers, authorities, additionals] = sections;
+        Ok(Message {
+            header: self.header.clone(),
+            questions,
+            answers,
+            authorities,
+            additionals,
+        })
+    }
+
+    fn records(&self, section: Section) -> SectionIter<'a, RecordRef<'a>> {
+        self.records_at(section, self.section_offset(section))
+    }
+
+    fn records_at(
+        &self,
+        section: Section,
+        start: Result<usize, DecodeError>,
+    ) -> SectionIter<'a, RecordRef<'a>> {
+        let count = match section {
+            Section::Question => self.header.qdcount,
+            Section::Answer => self.header.ancount,
+            Section::Authority => self.header.nscount,
+            Section::Additional => self.header.arcount,
+        };
+        SectionIter::new(self.buf, start, section, count, RecordRef::parse)
+    }
+
+    /// Finds where `section` starts by skipping over the preceding ones.
+    fn section_offset(&self, section: Section) -> Result<usize, DecodeError> {
+        let mut questions = self.questions();
+        for question in questions.by_ref() {
+            question?;
+        }
+        let mut offset = questions.offset();
+        for previous in [Section::Answer, Section::Authority] {
+            if previous == section {
+                break;
+            }
+            let mut records = self.records_at(previous, Ok(offset));
+            for record in records.by_ref() {
+                record?;
+            }
+            offset = records.offset();
+        }
+        Ok(offset)
+    }
+}
+
+impl TryFrom<MessageRef<'_>> for Message {
+    type Error = DecodeError;
+
+    fn try_from(message: MessageRef<'_>) -> Result<Self, Self::Error> {
+        message.to_message()
+    }
+}
+
+/// Parses one entry at an offset, returning it with the offset past it.
+type ParseFn<'a, T> = fn(&'a [u8], usize) -> Result<(T, usize), DecodeError>;
+
+/// Lazily parses the entries of one message section. Iteration stops after
+/// the first error.
+pub struct SectionIter<'a, T> {
+    buf: &'a [u8],
+    offset: usize,
+    error: Option<DecodeError>,
+    section: Section,
+    expected: u16,
+    found: u16,
+    parse: ParseFn<'a, T>,
+}
+
+impl<'a, T> SectionIter<'a, T> {
+    fn new(
+        buf: &'a [u8],
+        start: Result<usize, DecodeError>,
+        section: Section,
+        expected: u16,
+        parse: ParseFn<'a, T>,
+    ) -> Self {
+        let (offset, error) = match start {
+            Ok(offset) => (offset, None),
+            Err(e) => (e.offset(), Some(e)),
+        };
+        SectionIter {
+            buf,
+            offset,
+            error,
+            section,
+            expected,
+            found: 0,
+            parse,
+        }
+    }
+
+    /// Offset just past the last entry parsed so far.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<'a, T> Iterator for SectionIter<'a, T> {
+    type Item = Result<T, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(error) = self.error.take() {
+            self.found = self.expected;
+            return Some(Err(error));
+        }
+        if self.found >= self.expected {
+            return None;
+        }
+        if self.offset == self.buf.len() {
+            let error = DecodeError::CountMismatch {
+                offset: self.offset,
+                section: self.section,
+                expected: self.expected,
+                found: self.found,
+            };
+            self.found = self.expected;
+            return Some(Err(error));
+        }
+        match (self.parse)(self.buf, self.offset) {
+            Ok((entry, end)) => {
+                self.found += 1;
+                self.offset = end;
+                Some(Ok(entry))
+            }
+            Err(error) => {
+                self.found = self.expected;
+                Some(Err(error))
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.expected - self.found) as usize;
+        (0, Some(remaining + self.error.is_some() as usize))
+    }
+}
+
+/// A validated name inside a message buffer, read in place by following
+/// compression pointers.
+#[derive(Clone, Copy)]
+pub struct NameRef<'a> {
+    buf: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> NameRef<'a> {
+    /// Validates the name at `offset` and returns it together with the
+    /// offset just past its representation at that position.
+    ///
+    /// Pointers must point strictly backwards, which rules out both forward
+    /// references and loops.
+    pub fn parse(buf: &'a [u8], offset: usize) -> Result<(Self, usize), DecodeError> {
+        let mut pos = offset;
+        let mut end = None;
+        let mut hops = 0;
+        let mut wire_len = 1;
+
+        loop {
+            let len = *buf.get(pos).ok_or(DecodeError::Truncated { offset: pos })?;
+            if len == 0 {
+                pos += 1;
+                break;
+            }
+            match len & 0xC0 {
+                0xC0 => {
+                    let low = *buf
+                        .get(pos + 1)
+                        .ok_or(DecodeError::Truncated { offset: pos + 1 })?;
+                    let target = ((len & 0x3F) as usize) << 8 | low as usize;
+                    hops += 1;
+                    if target >= pos || hops > MAX_POINTER_HOPS {
+                        return Err(DecodeError::BadPointer {
+                            offset: pos,
+                            target,
+                        });
+                    }
+                    if end.is_none() {
+                        end = Some(pos + 2);
+                    }
+                    pos = target;
+                }
+                0x00 => {
+                    let label_end = pos + 1 + len as usize;
+                    if label_end > buf.len() {
+                        return Err(DecodeError::Truncated { offset: pos + 1 });
+                    }
+                    wire_len += len as usize + 1;
+                    if wire_len > MAX_NAME_LEN {
+                        return Err(DecodeError::NameTooLong { offset });
+                    }
+                    pos = label_end;
+                }
+                // 0x40 and 0x80 prefixes are extended label types (RFC 6891)
+                // that nothing uses; read as plain lengths they exceed 63.
+                _ => return Err(DecodeError::LabelTooLong { offset: pos }),
+            }
+        }
+        Ok((NameRef { buf, offset }, end.unwrap_or(pos)))
+    }
+
+    /// Offset of the name's first byte in the message.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn labels(&self) -> LabelIter<'a> {
+        LabelIter {
+            buf: self.buf,
+            pos: self.offset,
+        }
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.labels().next().is_none()
+    }
+
+    pub fn to_name(&self) -> Name {
+        // The labels were validated by `parse`, so this cannot fail.
+        Name::from_labels(self.labels()).unwrap_or_default()
+    }
+}
+
+impl PartialEq<Name> for NameRef<'_> {
+    fn eq(&self, other: &Name) -> bool {
+        let mut labels = self.labels();
+        other
+            .labels()
+            .all(|b| labels.next().is_some_and(|a| a.eq_ignore_ascii_case(b)))
+            && labels.next().is_none()
+    }
+}
+
+impl PartialEq for NameRef<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        let mut labels = self.labels();
+        other
+            .labels()
+            .all(|b| labels.next().is_some_and(|a| a.eq_ignore_ascii_case(b)))
+            && labels.next().is_none()
+    }
+}
+
+impl Eq for NameRef<'_> {}
+
+impl fmt::Display for NameRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_root() {
+            return f.write_str(".");
+        }
+        for label in self.labels() {
+            write_label(f, label)?;
+            f.write_str(".")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for NameRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "NameRef({})", self)
+    }
+}
+
+/// Iterates over the labels of a `NameRef`, following pointers.
+pub struct LabelIter<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Iterator for LabelIter<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let len = *self.buf.get(self.pos)?;
+            if len & 0xC0 == 0xC0 {
+                let low = *self.buf.get(self.pos + 1)?;
+                self.pos = ((len & 0x3F) as usize) << 8 | low as usize;
+                continue;
+            }
+            if len == 0 {
+                return None;
+            }
+            let label = self.buf.get(self.pos + 1..self.pos + 1 + len as usize)?;
+            self.pos += 1 + len as usize;
+            return Some(label);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct QuestionRef<'a> {
+    pub qname: NameRef<'a>,
+    pub qtype: RecordType,
+    pub qclass: RecordClass,
+}
+
+impl<'a> QuestionRef<'a> {
+    pub fn parse(buf: &'a [u8], offset: usize) -> Result<(Self, usize), DecodeError> {
+        let (qname, pos) = NameRef::parse(buf, offset)?;
+        let qtype = RecordType::from(u16_at(buf, pos)?);
+        let qclass = RecordClass::from(u16_at(buf, pos + 2)?);
+        let question = QuestionRef {
+            qname,
+            qtype,
+            qclass,
+        };
+        Ok((question, pos + 4))
+    }
+
+    pub fn to_question(&self) -> Question {
+        Question {
+            qname: self.qname.to_name(),
+            qtype: self.qtype,
+            qclass: self.qclass,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RecordRef<'a> {
+    buf: &'a [u8],
+    rdata_offset: usize,
+    pub name: NameRef<'a>,
+    pub rtype: RecordType,
+    pub rclass: RecordClass,
+    pub ttl: u32,
+    /// The raw RDATA; names inside it may be compressed pointers into the
+    /// rest of the message.
+    pub rdata: &'a [u8],
+}
+
+impl<'a> RecordRef<'a> {
+    pub fn parse(buf: &'a [u8], offset: usize) -> Result<(Self, usize), DecodeError> {
+        let (name, pos) = NameRef::parse(buf, offset)?;
+        let rtype = RecordType::from(u16_at(buf, pos)?);
+        let rclass = RecordClass::from(u16_at(buf, pos + 2)?);
+        let ttl = u32_at(buf, pos + 4)?;
+        let rdlength = u16_at(buf, pos + 8)? as usize;
+        let rdata_offset = pos + 10;
+        let rdata =
+            buf.get(rdata_offset..rdata_offset + rdlength)
+                .ok_or(DecodeError::Truncated {
+                    offset: rdata_offset,
+                })?;
+        let record = RecordRef {
+            buf,
+            rdata_offset,
+            name,
+            rtype,
+            rclass,
+            ttl,
+            rdata,
+        };
+        Ok((record, rdata_offset + rdlength))
+    }
+
+    /// Offset of the RDATA's first byte in the message.
+    pub fn rdata_offset(&self) -> usize {
+        self.rdata_offset
+    }
+
+    pub fn to_rdata(&self) -> Result<RData, DecodeError> {
+        // Decode from a view that ends with the RDATA: pointers still reach
+        // the earlier parts of the message, but no field can run past
+        // RDLENGTH into the next record.
+        let rdata_end = self.rdata_offset + self.rdata.len();
+        let mut reader = Cursor::new(&self.buf[..rdata_end]);
+        reader.set_position(self.rdata_offset as u64);
+        let mismatch = DecodeError::RDataLengthMismatch {
+            offset: self.rdata_offset,
+            rtype: self.rtype,
+        };
+        let rdata = match RData::read(&mut reader, self.rtype, self.rdata.len() as u16) {
+            Ok(rdata) => rdata,
+            Err(DecodeError::Truncated { .. }) => return Err(mismatch),
+            Err(e) => return Err(e),
+        };
+        if reader.position() as usize != rdata_end {
+            return Err(mismatch);
+        }
+        Ok(rdata)
+    }
+
+    pub fn to_record(&self) -> Result<Record, DecodeError> {
+        Ok(Record {
+            name: self.name.to_name(),
+            rtype: self.rtype,
+            rclass: self.rclass,
+            ttl: self.ttl,
+            rdata: self.to_rdata()?,
+        })
+    }
+}
@@ -0,0 +1,295 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Write};
+use std::str::FromStr;
+use thiserror::Error;
+
+pub const MAX_LABEL_LEN: usize = 63;
+pub const MAX_NAME_LEN: usize = 255;
+
+/// A fully qualified domain name, stored as its sequence of labels with the
+/// root label left implicit.
+///
+/// Comparison and hashing are ASCII case-insensitive, and `Ord` follows the
+/// canonical ordering of RFC 4034 section 6.1.
+#[derive(Clone, Default)]
+pub struct Name {
+    labels: Vec<Vec<u8>>,
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum NameError {
+    #[error("Label exceeds 63 octets")]
+    LabelTooLong,
+    #[error("Name exceeds 255 octets")]
+    NameTooLong,
+    #[error("Empty label in name")]
+    EmptyLabel,
+    #[error("Invalid escape sequence in name")]
+    InvalidEscape,
+    #[error("Relative name without an origin")]
+    MissingOrigin,
+}
+
+impl Name {
+    pub fn root() -> Self {
+        Name { labels: Vec::new() }
+    }
+
+    pub fn from_labels<I, L>(labels: I) -> Result<Self, NameError>
+    where
+        I: IntoIterator<Item = L>,
+        L: AsRef<[u8]>,
+    {
+        let name = Name {
+            labels: labels.into_iter().map(|l| l.as_ref().to_vec()).collect(),
+        };
+        name.validate()?;
+        Ok(name)
+    }
+
+    /// Parses a name in presentation format. Names without a trailing dot are
+    /// relative to `origin`, and `@` stands for the origin itself; without an
+    /// origin every name is taken as fully qualified.
+    pub fn parse(s: &str, origin: Option<&Name>) -> Result<Self, NameError> {
+        if s == "@" {
+            return origin.cloned().ok_or(NameError::MissingOrigin);
+        }
+        if s == "." {
+            return Ok(Name::root());
+        }
+        if s.is_empty() {
+            return Err(NameError::EmptyLabel);
+        }
+
+        let bytes = s.as_bytes();
+        let mut labels = Vec::new();
+        let mut label = Vec::new();
+        let mut absolute = false;
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\\' => {
+                    let escaped = bytes.get(i + 1).ok_or(NameError::InvalidEscape)?;
+                    if escaped.is_ascii_digit() {
+                        let digits = bytes.get(i + 1..i + 4).ok_or(NameError::InvalidEscape)?;
+                        if !digits.iter().all(u8::is_ascii_digit) {
+                            return Err(NameError::InvalidEscape);
+                        }
+                        let value = digits
+                            .iter()
+                            .fold(0u16, |acc, d| acc * 10 + (d - b'0') as u16);
+                        label.push(u8::try_from(value).map_err(|_| NameError::InvalidEscape)?);
+                        i += 4;
+                    } else {
+                        label.push(*escaped);
+                        i += 2;
+                    }
+                    continue;
+                }
+                b'.' => {
+                    if label.is_empty() {
+                        return Err(NameError::EmptyLabel);
+                    }
+                    labels.push(std::mem::take(&mut label));
+                    if i + 1 == bytes.len() {
+                        absolute = true;
+                    }
+                }
+                b => label.push(b),
+            }
+            i += 1;
+        }
+        if !label.is_empty() {
+            labels.push(label);
+        }
+
+        if !absolute {
+            if let Some(origin) = origin {
+                labels.extend(origin.labels.iter().cloned());
+            }
+        }
+        Name::from_labels(labels)
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// Iterates over the labels from the leftmost one, excluding the root.
+    pub fn labels(&self) -> impl DoubleEndedIterator<Item = &[u8]> + ExactSizeIterator {
+        self.labels.iter().map(Vec::as_slice)
+    }
+
+    pub fn label_count(&self) -> usize {
+        self.labels.len()
+    }
+
+    /// Length of the uncompressed wire representation, including the root.
+    pub fn wire_len(&self) -> usize {
+        self.labels.iter().map(|l| l.len() + 1).sum::<usize>() + 1
+    }
+
+    pub fn is_subdomain_of(&self, other: &Name) -> bool {
+        self.labels.len() >= other.labels.len()
+            && self
+                .labels
+                .iter()
+                .rev()
+                .zip(other.labels.iter().rev())
+                .all(|(a, b)| a.eq_ignore_ascii_case(b))
+    }
+
+    /// The name with its leftmost label removed, or `None` for the root.
+    pub fn parent(&self) -> Option<Name> {
+        if self.is_root() {
+            return None;
+        }
+        Some(Name {
+            labels: self.labels[1..].to_vec(),
+        })
+    }
+
+    /// The trailing `n` labels of this name, e.g. `suffix(2)` of
+    /// `www.example.com.` is `example.com.`.
+    pub fn suffix(&self, n: usize) -> Name {
+        let start = self.labels.len().saturating_sub(n);
+        Name {
+            labels: self.labels[start..].to_vec(),
+        }
+    }
+
+    pub fn prepend_label<L: AsRef<[u8]>>(&self, label: L) -> Result<Name, NameError> {
+        let mut labels = Vec::with_capacity(self.labels.len() + 1);
+        labels.push(label.as_ref().to_vec());
+        labels.extend(self.labels.iter().cloned());
+        Name::from_labels(labels)
+    }
+
+    pub fn to_lowercase(&self) -> Name {
+        Name {
+            labels: self.labels.iter().map(|l| l.to_ascii_lowercase()).collect(),
+        }
+    }
+
+    /// Writes the uncompressed wire representation.
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        for label in &self.labels {
+            writer.write_all(&[label.len() as u8])?;
+            writer.write_all(label)?;
+        }
+        writer.write_all(&[0u8])
+    }
+
+    pub fn to_wire(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.wire_len());
+        for label in &self.labels {
+            buf.push(label.len() as u8);
+            buf.extend_from_slice(label);
+        }
+        buf.push(0);
+        buf
+    }
+
+    fn validate(&self) -> Result<(), NameError> {
+        for label in &self.labels {
+            if label.is_empty() {
+                return Err(NameError::EmptyLabel);
+            }
+            if label.len() > MAX_LABEL_LEN {
+                return Err(NameError::LabelTooLong);
+            }
+        }
+        if self.wire_len() > MAX_NAME_LEN {
+            return Err(NameError::NameTooLong);
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Name {
+    type Err = NameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Name::parse(s, None)
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_root() {
+            return f.write_str(".");
+        }
+        for label in &self.labels {
+            write_label(f, label)?;
+            f.write_str(".")?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes a label in presentation format, escaping special and
+/// non-printable octets.
+pub(crate) fn write_label(f: &mut fmt::Formatter<'_>, label: &[u8]) -> fmt::Result {
+    for &b in label {
+        match b {
+            b'.' | b'\\' | b'"' | b'(' | b')' | b';' | b'@' | b'$' => write!(f, "\\{}", b as char)?,
+            0x21..=0x7E => write!(f, "{}", b as char)?,
+            _ => write!(f, "\\{:03}", b)?,
+        }
+    }
+    Ok(())
+}
+
+impl fmt::Debug for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Name({})", self)
+    }
+}
+
+impl PartialEq for Name {
+    fn eq(&self, other: &Self) -> bool {
+        self.labels.len() == other.labels.len()
+            && self
+                .labels
+                .iter()
+                .zip(&other.labels)
+                .all(|(a, b)| a.eq_ignore_ascii_case(b))
+    }
+}
+
+impl Eq for Name {}
+
+impl Hash for Name {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for label in &self.labels {
+            state.write_u8(label.len() as u8);
+            for b in label {
+                state.write_u8(b.to_ascii_lowercase());
+            }
+        }
+        state.write_u8(0);
+    }
+}
+
+impl PartialOrd for Name {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Name {
+    fn cmp(&self, other: &Self) -> Ordering {
+        for (a, b) in self.labels.iter().rev().zip(other.labels.iter().rev()) {
+            let ordering = a
+                .iter()
+                .map(u8::to_ascii_lowercase)
+                .cmp(b.iter().map(u8::to_ascii_lowercase));
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        self.labels.len().cmp(&other.labels.len())
+    }
+}
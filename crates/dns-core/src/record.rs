@@ -1,119 +1,153 @@
-
-use std::io::{self, Read, Write};
+use crate::compression::{compress_name, decompress_name};
+use crate::dnssec::{Csync, Dnskey, Ds, Nsec, Nsec3, Nsec3Param, Rrsig, Zonemd};
+use crate::encoding::{base64_encode, hex_encode};
+use crate::error::DecodeError;
+use crate::loc::Loc;
+use crate::message_ref::RecordRef;
+use crate::name::Name;
+use crate::security::{Caa, Cert, Sshfp, Tlsa};
+use crate::svcb::Svcb;
+use crate::types::{RecordClass, RecordType};
+use crate::wire::{read_character_string, read_slice, read_u16, read_u32, write_character_string};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Cursor, Seek, SeekFrom, Write};
 use std::net::{Ipv4Addr, Ipv6Addr};
-use thiserror::Error;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RData {
     A(Ipv4Addr),
     AAAA(Ipv6Addr),
-    CNAME(String),
+    CNAME(Name),
     MX {
         preference: u16,
-        exchange: String,
+        exchange: Name,
     },
-    NS(String),
+    NS(Name),
     SOA {
-        mname: String,
-        rname: String,
+        mname: Name,
+        rname: Name,
         serial: u32,
         refresh: u32,
         retry: u32,
         expire: u32,
         minimum: u32,
     },
-    TXT(String),
+    /// The <character-string>s of a TXT record, each at most 255 bytes.
+    TXT(Vec<Vec<u8>>),
+    PTR(Name),
+    SRV {
+        priority: u16,
+        weight: u16,
+        port: u16,
+        target: Name,
+    },
+    NAPTR {
+        order: u16,
+        preference: u16,
+        flags: Vec<u8>,
+        services: Vec<u8>,
+        regexp: Vec<u8>,
+        replacement: Name,
+    },
+    URI {
+        priority: u16,
+        weight: u16,
+        target: String,
+    },
+    CAA(Caa),
+    TLSA(Tlsa),
+    SMIMEA(Tlsa),
+    SSHFP(Sshfp),
+    OPENPGPKEY(Vec<u8>),
+    SVCB(Svcb),
+    HTTPS(Svcb),
+    DNSKEY(Dnskey),
+    CDNSKEY(Dnskey),
+    DS(Ds),
+    CDS(Ds),
+    RRSIG(Rrsig),
+    NSEC(Nsec),
+    NSEC3(Nsec3),
+    NSEC3PARAM(Nsec3Param),
+    DNAME(Name),
+    HINFO {
+        cpu: Vec<u8>,
+        os: Vec<u8>,
+    },
+    RP {
+        mbox: Name,
+        txt: Name,
+    },
+    LOC(Loc),
+    AFSDB {
+        subtype: u16,
+        hostname: Name,
+    },
+    CERT(Cert),
+    ZONEMD(Zonemd),
+    CSYNC(Csync),
     Raw(Vec<u8>), // For unsupported or unknown types
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Record {
-    pub name: String,
-    pub rtype: u16,
-    pub rclass: u16,
+    pub name: Name,
+    pub rtype: RecordType,
+    pub rclass: RecordClass,
     pub ttl: u32,
     pub rdata: RData,
 }
 
-impl Record {
-    pub fn read<R: Read>(reader: &mut R) -> Result<Self, RecordError> {
-        let name = read_qname(reader)?;
-        let rtype = read_u16(reader).unwrap();
-        let rclass = read_u16(reader).unwrap();
-        let ttl = read_u32(reader).unwrap();
-        let rdlength = read_u16(reader).unwrap();
-        let mut rdata_buf = vec![0u8; rdlength as usize];
-        reader.read_exact(&mut rdata_buf).unwrap();
+impl RData {
+    /// Builds TXT RDATA from arbitrary bytes, splitting them into
+    /// <character-string>s of at most 255 bytes.
+    pub fn txt<T: AsRef<[u8]>>(data: T) -> Self {
+        let data = data.as_ref();
+        if data.is_empty() {
+            return RData::TXT(vec![Vec::new()]);
+        }
+        RData::TXT(data.chunks(255).map(<[u8]>::to_vec).collect())
+    }
 
+    /// Decodes RDATA of type `rtype` occupying the next `rdlength` bytes.
+    /// The cursor may extend before the RDATA so compression pointers can be
+    /// followed, but must end where the RDATA ends.
+    pub fn read(
+        reader: &mut Cursor<&[u8]>,
+        rtype: RecordType,
+        rdlength: u16,
+    ) -> Result<Self, DecodeError> {
+        let offset = reader.position() as usize;
         let rdata = match rtype {
-            1 => {
-                // A
-                if rdlength != 4 {
-                    return Err(RecordError::InvalidRDataLength(rtype));
-                }
-                let ip = Ipv4Addr::new(rdata_buf[0], rdata_buf[1], rdata_buf[2], rdata_buf[3]);
-                RData::A(ip)
-            }
-            28 => {
-                // AAAA
-                if rdlength != 16 {
-                    return Err(RecordError::InvalidRDataLength(rtype));
-                }
-                let ip = Ipv6Addr::from([
-                    rdata_buf[0],
-                    rdata_buf[1],
-                    rdata_buf[2],
-                    rdata_buf[3],
-                    rdata_buf[4],
-                    rdata_buf[5],
-                    rdata_buf[6],
-                    rdata_buf[7],
-                    rdata_buf[8],
-                    rdata_buf[9],
-                    rdata_buf[10],
-                    rdata_buf[11],
-                    rdata_buf[12],
-                    rdata_buf[13],
-                    rdata_buf[14],
-                    rdata_buf[15],
-                ]);
-                RData::AAAA(ip)
+            RecordType::A => {
+                let octets: [u8; 4] = read_slice(reader, 4)?.try_into().unwrap_or_default();
+                RData::A(Ipv4Addr::from(octets))
             }
-            5 => {
-                // CNAME
-                let mut cursor = io::Cursor::new(&rdata_buf);
-                let cname = read_qname(&mut cursor)?;
-                RData::CNAME(cname)
+            RecordType::AAAA => {
+                let octets: [u8; 16] = read_slice(reader, 16)?.try_into().unwrap_or_default();
+                RData::AAAA(Ipv6Addr::from(octets))
             }
-            15 => {
-                // MX
-                if rdlength < 3 {
-                    return Err(RecordError::InvalidRDataLength(rtype));
-                }
-                let preference = ((rdata_buf[0] as u16) << 8) | (rdata_buf[1] as u16);
-                let mut cursor = io::Cursor::new(&rdata_buf[2..]);
-                let exchange = read_qname(&mut cursor)?;
+            RecordType::CNAME => RData::CNAME(decompress_name(reader)?),
+            RecordType::MX => {
+                let preference = read_u16(reader)?;
+                let exchange = decompress_name(reader)?;
                 RData::MX {
                     preference,
                     exchange,
                 }
             }
-            2 => {
-                // NS
-                let mut cursor = io::Cursor::new(&rdata_buf);
-                let ns = read_qname(&mut cursor)?;
-                RData::NS(ns)
-            }
-            6 => {
-                // SOA
-                let mut cursor = io::Cursor::new(&rdata_buf);
-                let mname = read_qname(&mut cursor)?;
-                let rname = read_qname(&mut cursor)?;
-                let serial = read_u32(&mut cursor).unwrap();
-                let refresh = read_u32(&mut cursor).unwrap();
-                let retry = read_u32(&mut cursor).unwrap();
-                let expire = read_u32(&mut cursor).unwrap();
-                let minimum = read_u32(&mut cursor).unwrap();
+            RecordType::NS => RData::NS(decompress_name(reader)?),
+            RecordType::SOA => {
+                let mname = decompress_name(reader)?;
+                let rname = decompress_name(reader)?;
+                let serial = read_u32(reader)?;
+                let refresh = read_u32(reader)?;
+                let retry = read_u32(reader)?;
+                let expire = read_u32(reader)?;
+                let minimum = read_u32(reader)?;
                 RData::SOA {
                     mname,
                     rname,
@@ -124,50 +158,144 @@ impl Record {
                     minimum,
                 }
             }
-            16 => {
-               
-                let txt =
-                    String::from_utf8(rdata_buf.clone()).map_err(|_| RecordError::InvalidUTF8)?;
-                RData::TXT(txt)
+            RecordType::TXT => {
+                let end = offset + rdlength as usize;
+                let mut strings = Vec::new();
+                while (reader.position() as usize) < end {
+                    strings.push(read_character_string(reader)?.to_vec());
+                }
+                RData::TXT(strings)
             }
-            _ => RData::Raw(rdata_buf),
+            RecordType::PTR => RData::PTR(decompress_name(reader)?),
+            RecordType::SRV => RData::SRV {
+                priority: read_u16(reader)?,
+                weight: read_u16(reader)?,
+                port: read_u16(reader)?,
+                target: decompress_name(reader)?,
+            },
+            RecordType::NAPTR => RData::NAPTR {
+                order: read_u16(reader)?,
+                preference: read_u16(reader)?,
+                flags: read_character_string(reader)?.to_vec(),
+                services: read_character_string(reader)?.to_vec(),
+                regexp: read_character_string(reader)?.to_vec(),
+                replacement: decompress_name(reader)?,
+            },
+            RecordType::URI => {
+                let priority = read_u16(reader)?;
+                let weight = read_u16(reader)?;
+                let target = read_slice(reader, (rdlength as usize).saturating_sub(4))?;
+                RData::URI {
+                    priority,
+                    weight,
+                    target: String::from_utf8(target.to_vec()).map_err(|_| {
+                        DecodeError::InvalidRData {
+                            offset,
+                            rtype,
+                            reason: "invalid UTF-8",
+                        }
+                    })?,
+                }
+            }
+            RecordType::CAA => RData::CAA(Caa::read(reader, rdlength)?),
+            RecordType::TLSA => RData::TLSA(Tlsa::read(reader, rdlength)?),
+            RecordType::SMIMEA => RData::SMIMEA(Tlsa::read(reader, rdlength)?),
+            RecordType::SSHFP => RData::SSHFP(Sshfp::read(reader, rdlength)?),
+            RecordType::OPENPGPKEY => {
+                RData::OPENPGPKEY(read_slice(reader, rdlength as usize)?.to_vec())
+            }
+            RecordType::SVCB => RData::SVCB(Svcb::read(reader, rtype, rdlength)?),
+            RecordType::HTTPS => RData::HTTPS(Svcb::read(reader, rtype, rdlength)?),
+            RecordType::DNSKEY => RData::DNSKEY(Dnskey::read(reader, rdlength)?),
+            RecordType::CDNSKEY => RData::CDNSKEY(Dnskey::read(reader, rdlength)?),
+            RecordType::DS => RData::DS(Ds::read(reader, rdlength)?),
+            RecordType::CDS => RData::CDS(Ds::read(reader, rdlength)?),
+            RecordType::RRSIG => RData::RRSIG(Rrsig::read(reader, rdlength)?),
+            RecordType::NSEC => RData::NSEC(Nsec::read(reader, rdlength)?),
+            RecordType::NSEC3 => RData::NSEC3(Nsec3::read(reader, rdlength)?),
+            RecordType::NSEC3PARAM => RData::NSEC3PARAM(Nsec3Param::read(reader)?),
+            RecordType::DNAME => RData::DNAME(decompress_name(reader)?),
+            RecordType::HINFO => RData::HINFO {
+                cpu: read_character_string(reader)?.to_vec(),
+                os: read_character_string(reader)?.to_vec(),
+            },
+            RecordType::RP => RData::RP {
+                mbox: decompress_name(reader)?,
+                txt: decompress_name(reader)?,
+            },
+            RecordType::LOC => RData::LOC(Loc::read(reader)?),
+            RecordType::AFSDB => RData::AFSDB {
+                subtype: read_u16(reader)?,
+                hostname: decompress_name(reader)?,
+            },
+            RecordType::CERT => RData::CERT(Cert::read(reader, rdlength)?),
+            RecordType::ZONEMD => RData::ZONEMD(Zonemd::read(reader, rdlength)?),
+            RecordType::CSYNC => RData::CSYNC(Csync::read(reader, rdlength)?),
+            _ => RData::Raw(read_slice(reader, rdlength as usize)?.to_vec()),
         };
-        Ok(Record {
-            name,
-            rtype,
-            rclass,
-            ttl,
-            rdata,
-        })
+        Ok(rdata)
     }
 
-    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
-        write_qname(writer, &self.name)?;
-        writer.write_all(&self.rtype.to_be_bytes())?;
-        writer.write_all(&self.rclass.to_be_bytes())?;
-        writer.write_all(&self.ttl.to_be_bytes())?;
-        let rdata_bytes = match &self.rdata {
-            RData::A(ip) => ip.octets().to_vec(),
-            RData::AAAA(ip) => ip.octets().to_vec(),
-            RData::CNAME(cname) => {
-                let mut buf = Vec::new();
-                write_qname(&mut buf, cname)?;
-                buf
+    /// The RDATA with the embedded names lowercased for the types listed in
+    /// RFC 4034 section 6.2, as corrected by RFC 6840 section 5.1.
+    pub fn to_canonical(&self) -> RData {
+        let mut rdata = self.clone();
+        match &mut rdata {
+            RData::CNAME(name) | RData::NS(name) | RData::PTR(name) | RData::DNAME(name) => {
+                *name = name.to_lowercase()
             }
+            RData::MX { exchange: name, .. }
+            | RData::SRV { target: name, .. }
+            | RData::NAPTR {
+                replacement: name, ..
+            }
+            | RData::AFSDB { hostname: name, .. } => *name = name.to_lowercase(),
+            RData::SOA { mname, rname, .. } => {
+                *mname = mname.to_lowercase();
+                *rname = rname.to_lowercase();
+            }
+            RData::RP { mbox, txt } => {
+                *mbox = mbox.to_lowercase();
+                *txt = txt.to_lowercase();
+            }
+            RData::RRSIG(rrsig) => rrsig.signer_name = rrsig.signer_name.to_lowercase(),
+            _ => {}
+        }
+        rdata
+    }
+
+    /// The canonical wire form of RFC 4034 section 6.2: uncompressed, with
+    /// names lowercased where the type calls for it.
+    pub fn to_canonical_bytes(&self) -> io::Result<Vec<u8>> {
+        self.to_canonical().to_bytes()
+    }
+
+    /// The uncompressed wire representation.
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        let mut buf = Cursor::new(Vec::new());
+        self.write_to(&mut buf, None)?;
+        Ok(buf.into_inner())
+    }
+
+    /// Writes the RDATA, compressing the names of the well-known types that
+    /// allow it (RFC 3597 section 4) when a compression map is given.
+    pub(crate) fn write_to<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        mut compression_map: Option<&mut HashMap<Name, u16>>,
+    ) -> io::Result<()> {
+        match self {
+            RData::A(ip) => writer.write_all(&ip.octets())?,
+            RData::AAAA(ip) => writer.write_all(&ip.octets())?,
+            RData::CNAME(cname) => write_name(writer, cname, compression_map)?,
             RData::MX {
                 preference,
                 exchange,
             } => {
-                let mut buf = Vec::new();
-                buf.extend(&preference.to_be_bytes());
-                write_qname(&mut buf, exchange)?;
-                buf
-            }
-            RData::NS(ns) => {
-                let mut buf = Vec::new();
-                write_qname(&mut buf, ns)?;
-                buf
+                writer.write_all(&preference.to_be_bytes())?;
+                write_name(writer, exchange, compression_map)?;
             }
+            RData::NS(ns) => write_name(writer, ns, compression_map)?,
             RData::SOA {
                 mname,
                 rname,
@@ -177,82 +305,258 @@ impl Record {
                 expire,
                 minimum,
             } => {
-                let mut buf = Vec::new();
-                write_qname(&mut buf, mname)?;
-                write_qname(&mut buf, rname)?;
-                buf.extend(&serial.to_be_bytes());
-                buf.extend(&refresh.to_be_bytes());
-                buf.extend(&retry.to_be_bytes());
-                buf.extend(&expire.to_be_bytes());
-                buf.extend(&minimum.to_be_bytes());
-                buf
+                write_name(writer, mname, compression_map.as_deref_mut())?;
+                write_name(writer, rname, compression_map)?;
+                writer.write_all(&serial.to_be_bytes())?;
+                writer.write_all(&refresh.to_be_bytes())?;
+                writer.write_all(&retry.to_be_bytes())?;
+                writer.write_all(&expire.to_be_bytes())?;
+                writer.write_all(&minimum.to_be_bytes())?;
             }
-            RData::TXT(txt) => {
-                let mut buf = Vec::new();
-                buf.extend(&(txt.len() as u8).to_be_bytes());
-                buf.extend(txt.as_bytes());
-                buf
+            RData::TXT(strings) => {
+                for string in strings {
+                    write_character_string(writer, string)?;
+                }
             }
-            RData::Raw(data) => data.clone(),
-        };
-        writer.write_all(&(rdata_bytes.len() as u16).to_be_bytes())?;
-        writer.write_all(&rdata_bytes)?;
+            RData::PTR(ptr) => write_name(writer, ptr, compression_map)?,
+            RData::SRV {
+                priority,
+                weight,
+                port,
+                target,
+            } => {
+                writer.write_all(&priority.to_be_bytes())?;
+                writer.write_all(&weight.to_be_bytes())?;
+                writer.write_all(&port.to_be_bytes())?;
+                // RFC 2782 forbids compressing the target.
+                target.write(writer)?;
+            }
+            RData::NAPTR {
+                order,
+                preference,
+                flags,
+                services,
+                regexp,
+                replacement,
+            } => {
+                writer.write_all(&order.to_be_bytes())?;
+                writer.write_all(&preference.to_be_bytes())?;
+                write_character_string(writer, flags)?;
+                write_character_string(writer, services)?;
+                write_character_string(writer, regexp)?;
+                replacement.write(writer)?;
+            }
+            RData::URI {
+                priority,
+                weight,
+                target,
+            } => {
+                writer.write_all(&priority.to_be_bytes())?;
+                writer.write_all(&weight.to_be_bytes())?;
+                writer.write_all(target.as_bytes())?;
+            }
+            RData::CAA(caa) => caa.write(writer)?,
+            RData::TLSA(tlsa) | RData::SMIMEA(tlsa) => tlsa.write(writer)?,
+            RData::SSHFP(sshfp) => sshfp.write(writer)?,
+            RData::OPENPGPKEY(key) => writer.write_all(key)?,
+            RData::SVCB(svcb) | RData::HTTPS(svcb) => svcb.write(writer)?,
+            RData::DNSKEY(key) | RData::CDNSKEY(key) => key.write(writer)?,
+            RData::DS(ds) | RData::CDS(ds) => ds.write(writer)?,
+            RData::RRSIG(rrsig) => rrsig.write(writer)?,
+            RData::NSEC(nsec) => nsec.write(writer)?,
+            RData::NSEC3(nsec3) => nsec3.write(writer)?,
+            RData::NSEC3PARAM(param) => param.write(writer)?,
+            // Types defined after RFC 1035 are written without compression.
+            RData::DNAME(target) => target.write(writer)?,
+            RData::HINFO { cpu, os } => {
+                write_character_string(writer, cpu)?;
+                write_character_string(writer, os)?;
+            }
+            RData::RP { mbox, txt } => {
+                mbox.write(writer)?;
+                txt.write(writer)?;
+            }
+            RData::LOC(loc) => loc.write(writer)?,
+            RData::AFSDB { subtype, hostname } => {
+                writer.write_all(&subtype.to_be_bytes())?;
+                hostname.write(writer)?;
+            }
+            RData::CERT(cert) => cert.write(writer)?,
+            RData::ZONEMD(zonemd) => zonemd.write(writer)?,
+            RData::CSYNC(csync) => csync.write(writer)?,
+            RData::Raw(data) => writer.write_all(data)?,
+        }
         Ok(())
     }
 }
 
-#[derive(Debug, Error)]
-pub enum RecordError {
-    #[error("Invalid RData length for type {0}")]
-    InvalidRDataLength(u16),
-    #[error("Invalid UTF-8 in RData")]
-    InvalidUTF8,
-    #[error("IO Error: {0}")]
-    IoError(#[from] io::Error),
+impl fmt::Display for RData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RData::A(ip) => write!(f, "{}", ip),
+            RData::AAAA(ip) => write!(f, "{}", ip),
+            RData::CNAME(name) | RData::NS(name) | RData::PTR(name) => write!(f, "{}", name),
+            RData::MX {
+                preference,
+                exchange,
+            } => write!(f, "{} {}", preference, exchange),
+            RData::SOA {
+                mname,
+                rname,
+                serial,
+                refresh,
+                retry,
+                expire,
+                minimum,
+            } => write!(
+                f,
+                "{} {} {} {} {} {} {}",
+                mname, rname, serial, refresh, retry, expire, minimum
+            ),
+            RData::TXT(strings) => {
+                for (i, string) in strings.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" ")?;
+                    }
+                    fmt_character_string(f, string)?;
+                }
+                Ok(())
+            }
+            RData::SRV {
+                priority,
+                weight,
+                port,
+                target,
+            } => write!(f, "{} {} {} {}", priority, weight, port, target),
+            RData::NAPTR {
+                order,
+                preference,
+                flags,
+                services,
+                regexp,
+                replacement,
+            } => {
+                write!(f, "{} {} ", order, preference)?;
+                for field in [flags, services, regexp] {
+                    fmt_character_string(f, field)?;
+                    f.write_str(" ")?;
+                }
+                write!(f, "{}", replacement)
+            }
+            RData::URI {
+                priority,
+                weight,
+                target,
+            } => {
+                write!(f, "{} {} ", priority, weight)?;
+                fmt_character_string(f, target.as_bytes())
+            }
+            RData::CAA(caa) => write!(f, "{}", caa),
+            RData::TLSA(tlsa) | RData::SMIMEA(tlsa) => write!(f, "{}", tlsa),
+            RData::SSHFP(sshfp) => write!(f, "{}", sshfp),
+            RData::OPENPGPKEY(key) => f.write_str(&base64_encode(key)),
+            RData::SVCB(svcb) | RData::HTTPS(svcb) => write!(f, "{}", svcb),
+            RData::DNSKEY(key) | RData::CDNSKEY(key) => write!(f, "{}", key),
+            RData::DS(ds) | RData::CDS(ds) => write!(f, "{}", ds),
+            RData::RRSIG(rrsig) => write!(f, "{}", rrsig),
+            RData::NSEC(nsec) => write!(f, "{}", nsec),
+            RData::NSEC3(nsec3) => write!(f, "{}", nsec3),
+            RData::NSEC3PARAM(param) => write!(f, "{}", param),
+            RData::DNAME(target) => write!(f, "{}", target),
+            RData::HINFO { cpu, os } => {
+                fmt_character_string(f, cpu)?;
+                f.write_str(" ")?;
+                fmt_character_string(f, os)
+            }
+            RData::RP { mbox, txt } => write!(f, "{} {}", mbox, txt),
+            RData::LOC(loc) => write!(f, "{}", loc),
+            RData::AFSDB { subtype, hostname } => write!(f, "{} {}", subtype, hostname),
+            RData::CERT(cert) => write!(f, "{}", cert),
+            RData::ZONEMD(zonemd) => write!(f, "{}", zonemd),
+            RData::CSYNC(csync) => write!(f, "{}", csync),
+            RData::Raw(data) if data.is_empty() => f.write_str("\\# 0"),
+            RData::Raw(data) => write!(f, "\\# {} {}", data.len(), hex_encode(data)),
+        }
+    }
 }
 
-fn read_qname<R: Read>(reader: &mut R) -> Result<String, RecordError> {
-    let mut labels = Vec::new();
-    loop {
-        let len = {
-            let mut len_buf = [0u8; 1];
-            reader.read_exact(&mut len_buf).unwrap();
-            len_buf[0]
-        };
-        if len == 0 {
-            break;
+/// Writes a <character-string> in quoted presentation format.
+pub(crate) fn fmt_character_string(f: &mut fmt::Formatter<'_>, data: &[u8]) -> fmt::Result {
+    f.write_str("\"")?;
+    for &b in data {
+        match b {
+            b'"' | b'\\' => write!(f, "\\{}", b as char)?,
+            0x20..=0x7E => write!(f, "{}", b as char)?,
+            _ => write!(f, "\\{:03}", b)?,
         }
-        let mut label = vec![0u8; len as usize];
-        reader.read_exact(&mut label).unwrap();
-        labels.push(String::from_utf8(label).map_err(|_| RecordError::InvalidUTF8)?);
     }
-    Ok(labels.join("."))
+    f.write_str("\"")
 }
 
-fn write_qname<W: Write>(writer: &mut W, qname: &str) -> io::Result<()> {
-    for label in qname.split('.') {
-        let len = label.len();
-        if len > 63 {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "Label too long",
-            ));
-        }
-        writer.write_all(&[len as u8])?;
-        writer.write_all(label.as_bytes())?;
+impl Record {
+    pub fn read(reader: &mut Cursor<&[u8]>) -> Result<Self, DecodeError> {
+        let (record, end) = RecordRef::parse(reader.get_ref(), reader.position() as usize)?;
+        let record = record.to_record()?;
+        reader.set_position(end as u64);
+        Ok(record)
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.name.write(writer)?;
+        writer.write_all(&u16::from(self.rtype).to_be_bytes())?;
+        writer.write_all(&u16::from(self.rclass).to_be_bytes())?;
+        writer.write_all(&self.ttl.to_be_bytes())?;
+        let rdata_bytes = self.rdata.to_bytes()?;
+        let rdlength = u16::try_from(rdata_bytes.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "RData too long"))?;
+        writer.write_all(&rdlength.to_be_bytes())?;
+        writer.write_all(&rdata_bytes)?;
+        Ok(())
+    }
+
+    /// Writes the record at the writer's current position in a message,
+    /// compressing the owner name and any names inside well-known RDATA.
+    pub fn write_compressed<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        compression_map: &mut HashMap<Name, u16>,
+    ) -> io::Result<()> {
+        compress_name(writer, &self.name, compression_map)?;
+        writer.write_all(&u16::from(self.rtype).to_be_bytes())?;
+        writer.write_all(&u16::from(self.rclass).to_be_bytes())?;
+        writer.write_all(&self.ttl.to_be_bytes())?;
+
+        let length_pos = writer.stream_position()?;
+        writer.write_all(&[0u8; 2])?;
+        self.rdata.write_to(writer, Some(compression_map))?;
+        let end_pos = writer.stream_position()?;
+        let rdlength = u16::try_from(end_pos - length_pos - 2)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "RData too long"))?;
+        writer.seek(SeekFrom::Start(length_pos))?;
+        writer.write_all(&rdlength.to_be_bytes())?;
+        writer.seek(SeekFrom::Start(end_pos))?;
+        Ok(())
     }
-    writer.write_all(&[0u8])?;
-    Ok(())
 }
 
-fn read_u16<R: Read>(reader: &mut R) -> Result<u16, io::Error> {
-    let mut buf = [0u8; 2];
-    reader.read_exact(&mut buf)?;
-    Ok(u16::from_be_bytes(buf))
+/// Formats the record as a master file line with tab-separated fields.
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}\t{}\t{}\t{}\t{}",
+            self.name, self.ttl, self.rclass, self.rtype, self.rdata
+        )
+    }
 }
 
-fn read_u32<R: Read>(reader: &mut R) -> Result<u32, io::Error> {
-    let mut buf = [0u8; 4];
-    reader.read_exact(&mut buf)?;
-    Ok(u32::from_be_bytes(buf))
+fn write_name<W: Write + Seek>(
+    writer: &mut W,
+    name: &Name,
+    compression_map: Option<&mut HashMap<Name, u16>>,
+) -> io::Result<()> {
+    match compression_map {
+        Some(map) => compress_name(writer, name, map),
+        None => name.write(writer),
+    }
 }
@@ -0,0 +1,166 @@
+use crate::name::Name;
+use crate::record::{RData, Record};
+use crate::types::{RecordClass, RecordType};
+use std::collections::HashMap;
+use std::io;
+
+/// The records sharing an owner name, type and class (RFC 2181 section 5).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RRset {
+    pub name: Name,
+    pub rtype: RecordType,
+    pub rclass: RecordClass,
+    pub ttl: u32,
+    pub rdata: Vec<RData>,
+}
+
+impl RRset {
+    pub fn new(name: Name, rtype: RecordType, rclass: RecordClass, ttl: u32) -> Self {
+        RRset {
+            name,
+            rtype,
+            rclass,
+            ttl,
+            rdata: Vec::new(),
+        }
+    }
+
+    /// Groups records into RRsets in order of first appearance. Duplicate
+    /// RDATA is dropped and each set takes the lowest TTL of its records,
+    /// as RFC 2181 section 5.2 requires of TTLs that differ.
+    pub fn from_records<I: IntoIterator<Item = Record>>(records: I) -> Vec<RRset> {
+        let mut rrsets: Vec<RRset> = Vec::new();
+        let mut index: HashMap<_, usize> = HashMap::new();
+        for record in records {
+            let key = (record.name.clone(), record.rtype, record.rclass);
+            match index.get(&key) {
+                Some(&i) => {
+                    rrsets[i].insert(record);
+                }
+                None => {
+                    index.insert(key, rrsets.len());
+                    rrsets.push(RRset {
+                        name: record.name,
+                        rtype: record.rtype,
+                        rclass: record.rclass,
+                        ttl: record.ttl,
+                        rdata: vec![record.rdata],
+                    });
+                }
+            }
+        }
+        rrsets
+    }
+
+    /// Whether `record` belongs to this RRset.
+    pub fn matches(&self, record: &Record) -> bool {
+        record.name == self.name && record.rtype == self.rtype && record.rclass == self.rclass
+    }
+
+    /// Adds the RDATA of a record belonging to this set, lowering the TTL to
+    /// the record's if it is smaller. Returns false if the record belongs to
+    /// another set or its RDATA is already present.
+    pub fn insert(&mut self, record: Record) -> bool {
+        if !self.matches(&record) {
+            return false;
+        }
+        self.ttl = self.ttl.min(record.ttl);
+        if self.rdata.contains(&record.rdata) {
+            return false;
+        }
+        self.rdata.push(record.rdata);
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.rdata.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rdata.is_empty()
+    }
+
+    /// Removes duplicate RDATA, keeping the first of each. Names in RDATA
+    /// compare case-insensitively.
+    pub fn dedup(&mut self) {
+        let mut unique: Vec<RData> = Vec::with_capacity(self.rdata.len());
+        for rdata in self.rdata.drain(..) {
+            if !unique.contains(&rdata) {
+                unique.push(rdata);
+            }
+        }
+        self.rdata = unique;
+    }
+
+    /// Sorts the RDATA into the canonical order of RFC 4034 section 6.3 and
+    /// removes duplicates, which have the same canonical form.
+    pub fn sort_canonical(&mut self) -> io::Result<()> {
+        let mut keyed = self
+            .rdata
+            .drain(..)
+            .map(|rdata| Ok((rdata.to_canonical_bytes()?, rdata)))
+            .collect::<io::Result<Vec<_>>>()?;
+        keyed.sort_by(|a, b| a.0.cmp(&b.0));
+        keyed.dedup_by(|a, b| a.0 == b.0);
+        self.rdata = keyed.into_iter().map(|(_, rdata)| rdata).collect();
+        Ok(())
+    }
+
+    /// The records of the set in canonical form and order, concatenated as
+    /// they are for signing (RFC 4034 section 6.3): lowercase owner name, no
+    /// compression, and the set's TTL.
+    pub fn to_canonical_wire(&self) -> io::Result<Vec<u8>> {
+        let mut rdata = self
+            .rdata
+            .iter()
+            .map(RData::to_canonical_bytes)
+            .collect::<io::Result<Vec<_>>>()?;
+        rdata.sort();
+        rdata.dedup();
+
+        let owner = self.name.to_lowercase().to_wire();
+        let mut buf = Vec::new();
+        for data in rdata {
+            let rdlength = u16::try_from(data.len())
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "RData too long"))?;
+            buf.extend_from_slice(&owner);
+            buf.extend_from_slice(&u16::from(self.rtype).to_be_bytes());
+            buf.extend_from_slice(&u16::from(self.rclass).to_be_bytes());
+            buf.extend_from_slice(&self.ttl.to_be_bytes());
+            buf.extend_from_slice(&rdlength.to_be_bytes());
+            buf.extend_from_slice(&data);
+        }
+        Ok(buf)
+    }
+
+    pub fn records(&self) -> impl Iterator<Item = Record> + '_ {
+        self.rdata.iter().map(|rdata| Record {
+            name: self.name.clone(),
+            rtype: self.rtype,
+            rclass: self.rclass,
+            ttl: self.ttl,
+            rdata: rdata.clone(),
+        })
+    }
+
+    pub fn into_records(self) -> Vec<Record> {
+        let RRset {
+            name,
+            rtype,
+            rclass,
+            ttl,
+            rdata,
+        } = self;
+        rdata
+            .into_iter()
+            .map(|rdata| Record {
+                name: name.clone(),
+                rtype,
+                rclass,
+                ttl,
+                rdata,
+            })
+            .collect()
+    }
+}
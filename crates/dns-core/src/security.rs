@@ -0,0 +1,246 @@
+use crate::encoding::{base64_encode, hex_encode};
+use crate::error::DecodeError;
+use crate::record::fmt_character_string;
+use crate::types::RecordType;
+use crate::wire::{read_slice, read_u16, read_u8};
+use std::fmt;
+use std::io::{self, Cursor, Write};
+
+/// CAA certification authority authorization (RFC 8659).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Caa {
+    pub flags: u8,
+    pub tag: String,
+    pub value: Vec<u8>,
+}
+
+impl Caa {
+    pub const ISSUER_CRITICAL: u8 = 0x80;
+
+    pub fn issuer_critical(&self) -> bool {
+        self.flags & Self::ISSUER_CRITICAL != 0
+    }
+
+    /// Tags are limited to ASCII letters and digits.
+    pub fn is_valid_tag(tag: &str) -> bool {
+        !tag.is_empty() && tag.len() <= 255 && tag.bytes().all(|b| b.is_ascii_alphanumeric())
+    }
+
+    pub fn value_str(&self) -> Option<&str> {
+        std::str::from_utf8(&self.value).ok()
+    }
+
+    pub(crate) fn read(reader: &mut Cursor<&[u8]>, rdlength: u16) -> Result<Self, DecodeError> {
+        let offset = reader.position() as usize;
+        let flags = read_u8(reader)?;
+        let tag_len = read_u8(reader)?;
+        let tag = std::str::from_utf8(read_slice(reader, tag_len as usize)?)
+            .ok()
+            .filter(|tag| Caa::is_valid_tag(tag))
+            .ok_or(DecodeError::InvalidRData {
+                offset,
+                rtype: RecordType::CAA,
+                reason: "invalid CAA tag",
+            })?
+            .to_string();
+        let value_len = (rdlength as usize).saturating_sub(2 + tag_len as usize);
+        let value = read_slice(reader, value_len)?.to_vec();
+        Ok(Caa { flags, tag, value })
+    }
+
+    pub(crate) fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        if !Caa::is_valid_tag(&self.tag) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Invalid CAA tag",
+            ));
+        }
+        writer.write_all(&[self.flags, self.tag.len() as u8])?;
+        writer.write_all(self.tag.as_bytes())?;
+        writer.write_all(&self.value)
+    }
+}
+
+impl fmt::Display for Caa {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} ", self.flags, self.tag)?;
+        fmt_character_string(f, &self.value)
+    }
+}
+
+/// Certificate association data shared by TLSA (RFC 6698) and SMIMEA
+/// (RFC 8162) records.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tlsa {
+    pub cert_usage: u8,
+    pub selector: u8,
+    pub matching_type: u8,
+    pub cert_data: Vec<u8>,
+}
+
+impl Tlsa {
+    pub const USAGE_PKIX_TA: u8 = 0;
+    pub const USAGE_PKIX_EE: u8 = 1;
+    pub const USAGE_DANE_TA: u8 = 2;
+    pub const USAGE_DANE_EE: u8 = 3;
+
+    pub const SELECTOR_FULL_CERT: u8 = 0;
+    pub const SELECTOR_SPKI: u8 = 1;
+
+    pub const MATCHING_FULL: u8 = 0;
+    pub const MATCHING_SHA256: u8 = 1;
+    pub const MATCHING_SHA512: u8 = 2;
+
+    pub(crate) fn read(reader: &mut Cursor<&[u8]>, rdlength: u16) -> Result<Self, DecodeError> {
+        let cert_usage = read_u8(reader)?;
+        let selector = read_u8(reader)?;
+        let matching_type = read_u8(reader)?;
+        let cert_data = read_slice(reader, (rdlength as usize).saturating_sub(3))?.to_vec();
+        Ok(Tlsa {
+            cert_usage,
+            selector,
+            matching_type,
+            cert_data,
+        })
+    }
+
+    pub(crate) fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&[self.cert_usage, self.selector, self.matching_type])?;
+        writer.write_all(&self.cert_data)
+    }
+}
+
+impl fmt::Display for Tlsa {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.cert_usage,
+            self.selector,
+            self.matching_type,
+            hex_encode(&self.cert_data)
+        )
+    }
+}
+
+/// SSH public key fingerprint (RFC 4255).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sshfp {
+    pub algorithm: u8,
+    pub fingerprint_type: u8,
+    pub fingerprint: Vec<u8>,
+}
+
+impl Sshfp {
+    pub const ALGORITHM_RSA: u8 = 1;
+    pub const ALGORITHM_DSA: u8 = 2;
+    pub const ALGORITHM_ECDSA: u8 = 3;
+    pub const ALGORITHM_ED25519: u8 = 4;
+
+    pub const FINGERPRINT_SHA1: u8 = 1;
+    pub const FINGERPRINT_SHA256: u8 = 2;
+
+    pub(crate) fn read(reader: &mut Cursor<&[u8]>, rdlength: u16) -> Result<Self, DecodeError> {
+        let algorithm = read_u8(reader)?;
+        let fingerprint_type = read_u8(reader)?;
+        let fingerprint = read_slice(reader, (rdlength as usize).saturating_sub(2))?.to_vec();
+        Ok(Sshfp {
+            algorithm,
+            fingerprint_type,
+            fingerprint,
+        })
+    }
+
+    pub(crate) fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&[self.algorithm, self.fingerprint_type])?;
+        writer.write_all(&self.fingerprint)
+    }
+}
+
+impl fmt::Display for Sshfp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.algorithm,
+            self.fingerprint_type,
+            hex_encode(&self.fingerprint)
+        )
+    }
+}
+
+/// CERT RDATA (RFC 4398).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cert {
+    pub cert_type: u16,
+    pub key_tag: u16,
+    pub algorithm: u8,
+    pub certificate: Vec<u8>,
+}
+
+impl Cert {
+    const TYPE_MNEMONICS: [(u16, &'static str); 10] = [
+        (1, "PKIX"),
+        (2, "SPKI"),
+        (3, "PGP"),
+        (4, "IPKIX"),
+        (5, "ISPKI"),
+        (6, "IPGP"),
+        (7, "ACPKIX"),
+        (8, "IACPKIX"),
+        (253, "URI"),
+        (254, "OID"),
+    ];
+
+    /// Parses a certificate type given as a mnemonic or a number.
+    pub fn parse_type(text: &str) -> Option<u16> {
+        Self::TYPE_MNEMONICS
+            .iter()
+            .find(|(_, mnemonic)| mnemonic.eq_ignore_ascii_case(text))
+            .map(|&(value, _)| value)
+            .or_else(|| text.parse().ok())
+    }
+
+    pub fn type_mnemonic(&self) -> Option<&'static str> {
+        Self::TYPE_MNEMONICS
+            .iter()
+            .find(|&&(value, _)| value == self.cert_type)
+            .map(|&(_, mnemonic)| mnemonic)
+    }
+
+    pub(crate) fn read(reader: &mut Cursor<&[u8]>, rdlength: u16) -> Result<Self, DecodeError> {
+        Ok(Cert {
+            cert_type: read_u16(reader)?,
+            key_tag: read_u16(reader)?,
+            algorithm: read_u8(reader)?,
+            certificate: read_slice(reader, (rdlength as usize).saturating_sub(5))?.to_vec(),
+        })
+    }
+
+    pub(crate) fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.cert_type.to_be_bytes())?;
+        writer.write_all(&self.key_tag.to_be_bytes())?;
+        writer.write_all(&[self.algorithm])?;
+        writer.write_all(&self.certificate)
+    }
+}
+
+impl fmt::Display for Cert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.type_mnemonic() {
+            Some(mnemonic) => f.write_str(mnemonic)?,
+            None => write!(f, "{}", self.cert_type)?,
+        }
+        write!(
+            f,
+            " {} {} {}",
+            self.key_tag,
+            self.algorithm,
+            base64_encode(&self.certificate)
+        )
+    }
+}
@@ -0,0 +1,29 @@
+//! Serde support for the types that have a presentation format, which are
+//! serialized as that text, e.g. `"example.com."` or `"AAAA"`.
+
+use crate::name::Name;
+use crate::svcb::SvcParamKey;
+use crate::types::{Opcode, Rcode, RecordClass, RecordType};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+macro_rules! serde_via_str {
+    ($($ty:ty),*) => {
+        $(
+            impl Serialize for $ty {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.collect_str(self)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let text = String::deserialize(deserializer)?;
+                    text.parse().map_err(D::Error::custom)
+                }
+            }
+        )*
+    };
+}
+
+serde_via_str!(Name, RecordType, RecordClass, Opcode, Rcode, SvcParamKey);
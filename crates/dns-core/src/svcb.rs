@@ -0,0 +1,529 @@
+use crate::compression::decompress_name;
+use crate::encoding::{base64_decode, base64_encode};
+use crate::error::DecodeError;
+use crate::name::Name;
+use crate::record::fmt_character_string;
+use crate::types::RecordType;
+use crate::wire::{read_character_string, read_slice, read_u16, write_character_string};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Cursor, Write};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum SvcbError {
+    #[error("Unknown SvcParamKey: {0}")]
+    UnknownKey(String),
+    #[error("Invalid value for SvcParam {0}")]
+    InvalidValue(SvcParamKey),
+    #[error("SvcParam {0} appears more than once")]
+    DuplicateKey(SvcParamKey),
+    #[error("SvcParams are not in strictly increasing key order")]
+    KeyOrder,
+    #[error("Mandatory SvcParam {0} is missing")]
+    MissingMandatory(SvcParamKey),
+    #[error("no-default-alpn requires alpn")]
+    NoDefaultAlpnWithoutAlpn,
+}
+
+/// SvcParamKey values from the IANA registry (RFC 9460 section 14.3).
+#[derive(Debug, Clone, Copy)]
+pub enum SvcParamKey {
+    Mandatory,
+    Alpn,
+    NoDefaultAlpn,
+    Port,
+    Ipv4Hint,
+    Ech,
+    Ipv6Hint,
+    DohPath,
+    Unknown(u16),
+}
+
+impl SvcParamKey {
+    pub fn mnemonic(&self) -> Option<&'static str> {
+        match SvcParamKey::from(u16::from(*self)) {
+            SvcParamKey::Mandatory => Some("mandatory"),
+            SvcParamKey::Alpn => Some("alpn"),
+            SvcParamKey::NoDefaultAlpn => Some("no-default-alpn"),
+            SvcParamKey::Port => Some("port"),
+            SvcParamKey::Ipv4Hint => Some("ipv4hint"),
+            SvcParamKey::Ech => Some("ech"),
+            SvcParamKey::Ipv6Hint => Some("ipv6hint"),
+            SvcParamKey::DohPath => Some("dohpath"),
+            SvcParamKey::Unknown(_) => None,
+        }
+    }
+}
+
+impl From<u16> for SvcParamKey {
+    fn from(value: u16) -> Self {
+        match value {
+            0 => SvcParamKey::Mandatory,
+            1 => SvcParamKey::Alpn,
+            2 => SvcParamKey::NoDefaultAlpn,
+            3 => SvcParamKey::Port,
+            4 => SvcParamKey::Ipv4Hint,
+            5 => SvcParamKey::Ech,
+            6 => SvcParamKey::Ipv6Hint,
+            7 => SvcParamKey::DohPath,
+            value => SvcParamKey::Unknown(value),
+        }
+    }
+}
+
+impl From<SvcParamKey> for u16 {
+    fn from(key: SvcParamKey) -> Self {
+        match key {
+            SvcParamKey::Mandatory => 0,
+            SvcParamKey::Alpn => 1,
+            SvcParamKey::NoDefaultAlpn => 2,
+            SvcParamKey::Port => 3,
+            SvcParamKey::Ipv4Hint => 4,
+            SvcParamKey::Ech => 5,
+            SvcParamKey::Ipv6Hint => 6,
+            SvcParamKey::DohPath => 7,
+            SvcParamKey::Unknown(value) => value,
+        }
+    }
+}
+
+impl PartialEq for SvcParamKey {
+    fn eq(&self, other: &Self) -> bool {
+        u16::from(*self) == u16::from(*other)
+    }
+}
+
+impl Eq for SvcParamKey {}
+
+impl Hash for SvcParamKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        u16::from(*self).hash(state)
+    }
+}
+
+impl PartialOrd for SvcParamKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SvcParamKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        u16::from(*self).cmp(&u16::from(*other))
+    }
+}
+
+impl fmt::Display for SvcParamKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.mnemonic() {
+            Some(mnemonic) => f.write_str(mnemonic),
+            None => write!(f, "key{}", u16::from(*self)),
+        }
+    }
+}
+
+impl FromStr for SvcParamKey {
+    type Err = SvcbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let key = match s {
+            "mandatory" => SvcParamKey::Mandatory,
+            "alpn" => SvcParamKey::Alpn,
+            "no-default-alpn" => SvcParamKey::NoDefaultAlpn,
+            "port" => SvcParamKey::Port,
+            "ipv4hint" => SvcParamKey::Ipv4Hint,
+            "ech" => SvcParamKey::Ech,
+            "ipv6hint" => SvcParamKey::Ipv6Hint,
+            "dohpath" => SvcParamKey::DohPath,
+            _ => s
+                .strip_prefix("key")
+                .filter(|digits| !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()))
+                .and_then(|digits| digits.parse::<u16>().ok())
+                // key65535 is reserved as an "invalid key".
+                .filter(|&value| value != u16::MAX)
+                .map(SvcParamKey::from)
+                .ok_or_else(|| SvcbError::UnknownKey(s.to_string()))?,
+        };
+        Ok(key)
+    }
+}
+
+/// A single service parameter of an SVCB or HTTPS record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SvcParam {
+    Mandatory(Vec<SvcParamKey>),
+    Alpn(Vec<Vec<u8>>),
+    NoDefaultAlpn,
+    Port(u16),
+    Ipv4Hint(Vec<Ipv4Addr>),
+    Ech(Vec<u8>),
+    Ipv6Hint(Vec<Ipv6Addr>),
+    DohPath(String),
+    Unknown(u16, Vec<u8>),
+}
+
+impl SvcParam {
+    pub fn key(&self) -> SvcParamKey {
+        match self {
+            SvcParam::Mandatory(_) => SvcParamKey::Mandatory,
+            SvcParam::Alpn(_) => SvcParamKey::Alpn,
+            SvcParam::NoDefaultAlpn => SvcParamKey::NoDefaultAlpn,
+            SvcParam::Port(_) => SvcParamKey::Port,
+            SvcParam::Ipv4Hint(_) => SvcParamKey::Ipv4Hint,
+            SvcParam::Ech(_) => SvcParamKey::Ech,
+            SvcParam::Ipv6Hint(_) => SvcParamKey::Ipv6Hint,
+            SvcParam::DohPath(_) => SvcParamKey::DohPath,
+            SvcParam::Unknown(key, _) => SvcParamKey::from(*key),
+        }
+    }
+
+    /// Builds a parameter from its presentation form. `value` is the
+    /// SvcParamValue after <character-string> escapes have been decoded, or
+    /// `None` when the key appeared without `=`.
+    pub fn from_presentation(key: &str, value: Option<&[u8]>) -> Result<Self, SvcbError> {
+        let key: SvcParamKey = key.parse()?;
+        let invalid = || SvcbError::InvalidValue(key);
+        let text = || {
+            value
+                .and_then(|v| std::str::from_utf8(v).ok())
+                .filter(|v| !v.is_empty())
+                .ok_or_else(invalid)
+        };
+        let param = match key {
+            SvcParamKey::Mandatory => {
+                let mut keys = text()?
+                    .split(',')
+                    .map(SvcParamKey::from_str)
+                    .collect::<Result<Vec<_>, _>>()?;
+                // Any order is accepted here; the wire format requires sorted keys.
+                keys.sort();
+                SvcParam::Mandatory(keys)
+            }
+            SvcParamKey::Alpn => {
+                SvcParam::Alpn(split_value_list(value.ok_or_else(invalid)?).ok_or_else(invalid)?)
+            }
+            SvcParamKey::NoDefaultAlpn => match value {
+                None | Some([]) => SvcParam::NoDefaultAlpn,
+                Some(_) => return Err(invalid()),
+            },
+            SvcParamKey::Port => SvcParam::Port(text()?.parse().map_err(|_| invalid())?),
+            SvcParamKey::Ipv4Hint => SvcParam::Ipv4Hint(
+                text()?
+                    .split(',')
+                    .map(|a| a.parse().map_err(|_| invalid()))
+                    .collect::<Result<_, _>>()?,
+            ),
+            SvcParamKey::Ech => SvcParam::Ech(base64_decode(text()?).map_err(|_| invalid())?),
+            SvcParamKey::Ipv6Hint => SvcParam::Ipv6Hint(
+                text()?
+                    .split(',')
+                    .map(|a| a.parse().map_err(|_| invalid()))
+                    .collect::<Result<_, _>>()?,
+            ),
+            SvcParamKey::DohPath => SvcParam::DohPath(text()?.to_string()),
+            SvcParamKey::Unknown(key) => SvcParam::Unknown(key, value.unwrap_or_default().to_vec()),
+        };
+        param.check_value()?;
+        Ok(param)
+    }
+
+    /// Decodes the wire-format SvcParamValue for `key`, returning `None` if
+    /// it is malformed.
+    fn from_wire(key: SvcParamKey, value: &[u8]) -> Option<Self> {
+        let param = match key {
+            SvcParamKey::Mandatory => SvcParam::Mandatory(
+                value
+                    .chunks(2)
+                    .map(|k| Some(SvcParamKey::from(u16::from_be_bytes(k.try_into().ok()?))))
+                    .collect::<Option<_>>()?,
+            ),
+            SvcParamKey::Alpn => {
+                let mut reader = Cursor::new(value);
+                let mut ids = Vec::new();
+                while (reader.position() as usize) < value.len() {
+                    ids.push(read_character_string(&mut reader).ok()?.to_vec());
+                }
+                SvcParam::Alpn(ids)
+            }
+            SvcParamKey::NoDefaultAlpn => SvcParam::NoDefaultAlpn,
+            SvcParamKey::Port => SvcParam::Port(u16::from_be_bytes(value.try_into().ok()?)),
+            SvcParamKey::Ipv4Hint => SvcParam::Ipv4Hint(
+                value
+                    .chunks(4)
+                    .map(|a| Some(Ipv4Addr::from(<[u8; 4]>::try_from(a).ok()?)))
+                    .collect::<Option<_>>()?,
+            ),
+            SvcParamKey::Ech => SvcParam::Ech(value.to_vec()),
+            SvcParamKey::Ipv6Hint => SvcParam::Ipv6Hint(
+                value
+                    .chunks(16)
+                    .map(|a| Some(Ipv6Addr::from(<[u8; 16]>::try_from(a).ok()?)))
+                    .collect::<Option<_>>()?,
+            ),
+            SvcParamKey::DohPath => SvcParam::DohPath(String::from_utf8(value.to_vec()).ok()?),
+            SvcParamKey::Unknown(key) => SvcParam::Unknown(key, value.to_vec()),
+        };
+        if param == SvcParam::NoDefaultAlpn && !value.is_empty() {
+            return None;
+        }
+        param.check_value().ok()?;
+        Some(param)
+    }
+
+    /// Checks the value-level rules of RFC 9460 section 7: lists must be
+    /// non-empty, alpn ids non-empty, and mandatory keys strictly increasing
+    /// without listing `mandatory` itself.
+    fn check_value(&self) -> Result<(), SvcbError> {
+        let valid = match self {
+            SvcParam::Mandatory(keys) => {
+                !keys.is_empty()
+                    && !keys.contains(&SvcParamKey::Mandatory)
+                    && keys.windows(2).all(|pair| pair[0] < pair[1])
+            }
+            SvcParam::Alpn(ids) => {
+                !ids.is_empty() && ids.iter().all(|id| !id.is_empty() && id.len() <= 255)
+            }
+            SvcParam::Ipv4Hint(addrs) => !addrs.is_empty(),
+            SvcParam::Ipv6Hint(addrs) => !addrs.is_empty(),
+            SvcParam::DohPath(path) => !path.is_empty(),
+            SvcParam::Unknown(key, _) => *key != u16::MAX,
+            _ => true,
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(SvcbError::InvalidValue(self.key()))
+        }
+    }
+
+    fn write_value<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        match self {
+            SvcParam::Mandatory(keys) => {
+                for key in keys {
+                    writer.write_all(&u16::from(*key).to_be_bytes())?;
+                }
+            }
+            SvcParam::Alpn(ids) => {
+                for id in ids {
+                    write_character_string(writer, id)?;
+                }
+            }
+            SvcParam::NoDefaultAlpn => {}
+            SvcParam::Port(port) => writer.write_all(&port.to_be_bytes())?,
+            SvcParam::Ipv4Hint(addrs) => {
+                for addr in addrs {
+                    writer.write_all(&addr.octets())?;
+                }
+            }
+            SvcParam::Ech(config) => writer.write_all(config)?,
+            SvcParam::Ipv6Hint(addrs) => {
+                for addr in addrs {
+                    writer.write_all(&addr.octets())?;
+                }
+            }
+            SvcParam::DohPath(path) => writer.write_all(path.as_bytes())?,
+            SvcParam::Unknown(_, value) => writer.write_all(value)?,
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SvcParam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.key())?;
+        match self {
+            SvcParam::Mandatory(keys) => {
+                let keys: Vec<String> = keys.iter().map(ToString::to_string).collect();
+                write!(f, "={}", keys.join(","))
+            }
+            SvcParam::Alpn(ids) => {
+                let mut list = Vec::new();
+                for (i, id) in ids.iter().enumerate() {
+                    if i > 0 {
+                        list.push(b',');
+                    }
+                    for &b in id {
+                        if b == b',' || b == b'\\' {
+                            list.push(b'\\');
+                        }
+                        list.push(b);
+                    }
+                }
+                f.write_str("=")?;
+                fmt_character_string(f, &list)
+            }
+            SvcParam::NoDefaultAlpn => Ok(()),
+            SvcParam::Port(port) => write!(f, "={}", port),
+            SvcParam::Ipv4Hint(addrs) => {
+                let addrs: Vec<String> = addrs.iter().map(ToString::to_string).collect();
+                write!(f, "={}", addrs.join(","))
+            }
+            SvcParam::Ech(config) => write!(f, "={}", base64_encode(config)),
+            SvcParam::Ipv6Hint(addrs) => {
+                let addrs: Vec<String> = addrs.iter().map(ToString::to_string).collect();
+                write!(f, "={}", addrs.join(","))
+            }
+            SvcParam::DohPath(path) => {
+                f.write_str("=")?;
+                fmt_character_string(f, path.as_bytes())
+            }
+            SvcParam::Unknown(_, value) if value.is_empty() => Ok(()),
+            SvcParam::Unknown(_, value) => {
+                f.write_str("=")?;
+                fmt_character_string(f, value)
+            }
+        }
+    }
+}
+
+/// Splits a comma-separated value list in which `\,` and `\\` stand for a
+/// literal comma and backslash (RFC 9460 appendix A.1).
+fn split_value_list(value: &[u8]) -> Option<Vec<Vec<u8>>> {
+    let mut items = vec![Vec::new()];
+    let mut bytes = value.iter();
+    while let Some(&b) = bytes.next() {
+        match b {
+            b'\\' => match bytes.next() {
+                Some(&escaped @ (b',' | b'\\')) => items.last_mut()?.push(escaped),
+                _ => return None,
+            },
+            b',' => items.push(Vec::new()),
+            b => items.last_mut()?.push(b),
+        }
+    }
+    Some(items)
+}
+
+/// RDATA shared by SVCB and HTTPS records (RFC 9460). A priority of zero
+/// selects AliasMode; any other value is ServiceMode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Svcb {
+    pub priority: u16,
+    pub target: Name,
+    pub params: Vec<SvcParam>,
+}
+
+impl Svcb {
+    pub fn is_alias(&self) -> bool {
+        self.priority == 0
+    }
+
+    pub fn param(&self, key: SvcParamKey) -> Option<&SvcParam> {
+        self.params.iter().find(|p| p.key() == key)
+    }
+
+    /// Sorts the parameters into wire order, rejecting duplicate keys.
+    pub fn sort_params(&mut self) -> Result<(), SvcbError> {
+        self.params.sort_by_key(SvcParam::key);
+        match self
+            .params
+            .windows(2)
+            .find(|pair| pair[0].key() == pair[1].key())
+        {
+            Some(pair) => Err(SvcbError::DuplicateKey(pair[0].key())),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks that the parameters are in strictly increasing key order, that
+    /// every value is well formed, and that the keys listed in `mandatory`
+    /// and the `alpn` required by `no-default-alpn` are present.
+    pub fn validate(&self) -> Result<(), SvcbError> {
+        if self
+            .params
+            .windows(2)
+            .any(|pair| pair[0].key() >= pair[1].key())
+        {
+            return Err(SvcbError::KeyOrder);
+        }
+        for param in &self.params {
+            param.check_value()?;
+        }
+        if let Some(SvcParam::Mandatory(keys)) = self.param(SvcParamKey::Mandatory) {
+            if let Some(&missing) = keys.iter().find(|&&key| self.param(key).is_none()) {
+                return Err(SvcbError::MissingMandatory(missing));
+            }
+        }
+        if self.param(SvcParamKey::NoDefaultAlpn).is_some()
+            && self.param(SvcParamKey::Alpn).is_none()
+        {
+            return Err(SvcbError::NoDefaultAlpnWithoutAlpn);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn read(
+        reader: &mut Cursor<&[u8]>,
+        rtype: RecordType,
+        rdlength: u16,
+    ) -> Result<Self, DecodeError> {
+        let start = reader.position() as usize;
+        let end = start + rdlength as usize;
+        let invalid = |offset, reason| DecodeError::InvalidRData {
+            offset,
+            rtype,
+            reason,
+        };
+
+        let priority = read_u16(reader)?;
+        let target = decompress_name(reader)?;
+        let mut params: Vec<SvcParam> = Vec::new();
+        while (reader.position() as usize) < end {
+            let offset = reader.position() as usize;
+            let key = SvcParamKey::from(read_u16(reader)?);
+            let len = read_u16(reader)?;
+            let value = read_slice(reader, len as usize)?;
+            if params.last().is_some_and(|last| last.key() >= key) {
+                return Err(invalid(offset, "SvcParam keys out of order"));
+            }
+            params.push(
+                SvcParam::from_wire(key, value)
+                    .ok_or_else(|| invalid(offset, "invalid SvcParam value"))?,
+            );
+        }
+
+        let svcb = Svcb {
+            priority,
+            target,
+            params,
+        };
+        svcb.validate()
+            .map_err(|_| invalid(start, "inconsistent SvcParams"))?;
+        Ok(svcb)
+    }
+
+    /// Writes the RDATA. The target name is never compressed.
+    pub(crate) fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.validate()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        writer.write_all(&self.priority.to_be_bytes())?;
+        self.target.write(writer)?;
+        for param in &self.params {
+            let mut value = Vec::new();
+            param.write_value(&mut value)?;
+            let len = u16::try_from(value.len()).map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidInput, "SvcParamValue too long")
+            })?;
+            writer.write_all(&u16::from(param.key()).to_be_bytes())?;
+            writer.write_all(&len.to_be_bytes())?;
+            writer.write_all(&value)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Svcb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.priority, self.target)?;
+        for param in &self.params {
+            write!(f, " {}", param)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,677 @@
+//! Reading of the master file presentation format (RFC 1035 section 5),
+//! shared by the `FromStr` impls and the zone parser.
+
+use crate::dnssec::{
+    parse_timestamp, Csync, Dnskey, Ds, Nsec, Nsec3, Nsec3Param, Rrsig, TypeBitmap, Zonemd,
+};
+use crate::encoding::{base32hex_decode, base64_decode, hex_decode};
+use crate::loc::LocError;
+use crate::name::{Name, NameError};
+use crate::question::Question;
+use crate::record::{RData, Record};
+use crate::security::{Caa, Cert, Sshfp, Tlsa};
+use crate::svcb::{SvcParam, Svcb, SvcbError};
+use crate::types::{RecordClass, RecordType};
+use std::io::Cursor;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    #[error("{0}")]
+    Invalid(String),
+    #[error("Invalid name: {0}")]
+    Name(#[from] NameError),
+    #[error("Invalid SvcParams: {0}")]
+    Svcb(#[from] SvcbError),
+}
+
+/// A whitespace-separated field of a master file line. Quotes are stripped
+/// from quoted fields but escape sequences are left in place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub text: &'a str,
+    pub quoted: bool,
+}
+
+/// Splits a line into tokens, honouring quoted strings, backslash escapes and
+/// `;` comments. Unquoted parentheses are returned as tokens of their own.
+pub fn tokenize(line: &str) -> Result<Vec<Token<'_>>, ParseError> {
+    let bytes = line.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b if b.is_ascii_whitespace() => i += 1,
+            b';' => break,
+            b'(' | b')' => {
+                tokens.push(Token {
+                    text: &line[i..i + 1],
+                    quoted: false,
+                });
+                i += 1;
+            }
+            b'"' => {
+                let start = i + 1;
+                let mut end = start;
+                loop {
+                    match bytes.get(end) {
+                        Some(b'"') => break,
+                        Some(b'\\') => end = skip_escape(line, end),
+                        Some(_) => end += 1,
+                        None => {
+                            return Err(ParseError::Invalid(format!(
+                                "Unterminated quoted string: {}",
+                                line
+                            )))
+                        }
+                    }
+                }
+                tokens.push(Token {
+                    text: &line[start..end],
+                    quoted: true,
+                });
+                i = end + 1;
+            }
+            _ => {
+                let start = i;
+                while i < bytes.len() && !bytes[i].is_ascii_whitespace() {
+                    match bytes[i] {
+                        b'\\' => i = skip_escape(line, i),
+                        b'"' | b';' | b'(' | b')' => break,
+                        _ => i += 1,
+                    }
+                }
+                let end = i.min(bytes.len());
+                tokens.push(Token {
+                    text: &line[start..end],
+                    quoted: false,
+                });
+                i = end;
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Returns the offset past the backslash at `i` and the character it escapes.
+fn skip_escape(line: &str, i: usize) -> usize {
+    let escaped = line[i + 1..].chars().next().map_or(0, char::len_utf8);
+    i + 1 + escaped
+}
+
+/// Decodes the `\X` and `\DDD` escapes of a <character-string>.
+pub fn unescape(text: &str) -> Result<Vec<u8>, ParseError> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'\\' {
+            out.push(bytes[i]);
+            i += 1;
+            continue;
+        }
+        let invalid = || ParseError::Invalid(format!("Invalid escape in: {}", text));
+        match bytes.get(i + 1) {
+            Some(d) if d.is_ascii_digit() => {
+                let digits = bytes.get(i + 1..i + 4).ok_or_else(invalid)?;
+                let value = std::str::from_utf8(digits)
+                    .ok()
+                    .and_then(|d| d.parse::<u8>().ok())
+                    .ok_or_else(invalid)?;
+                out.push(value);
+                i += 4;
+            }
+            Some(&c) => {
+                out.push(c);
+                i += 2;
+            }
+            None => return Err(invalid()),
+        }
+    }
+    Ok(out)
+}
+
+/// Parses the tokens of one entry, `<owner> [<ttl>] [<class>] <type>
+/// <rdata...>` with TTL and class in either order. The owner name is taken
+/// from the first token unless `owner` is given, and `default_ttl` applies
+/// when the entry has no TTL.
+pub fn parse_record(
+    tokens: &[Token],
+    owner: Option<Name>,
+    origin: Option<&Name>,
+    default_ttl: u32,
+) -> Result<Record, ParseError> {
+    let entry = || {
+        let text: Vec<&str> = tokens.iter().map(|t| t.text).collect();
+        text.join(" ")
+    };
+    let mut tokens = tokens.iter();
+    let name = match owner {
+        Some(owner) => owner,
+        None => {
+            let owner = tokens.next().ok_or_else(|| {
+                ParseError::Invalid(format!("Failed to parse record: {}", entry()))
+            })?;
+            Name::parse(owner.text, origin)?
+        }
+    };
+
+    let mut ttl = None;
+    let mut rclass = None;
+    let rtype = loop {
+        let token = tokens
+            .next()
+            .ok_or_else(|| ParseError::Invalid(format!("Missing record type: {}", entry())))?;
+        if ttl.is_none() {
+            if let Some(value) = parse_ttl(token.text) {
+                ttl = Some(value);
+                continue;
+            }
+        }
+        if rclass.is_none() {
+            if let Ok(class) = token.text.parse::<RecordClass>() {
+                rclass = Some(class);
+                continue;
+            }
+        }
+        break token
+            .text
+            .parse::<RecordType>()
+            .map_err(|_| ParseError::Invalid(format!("Unknown record type: {}", token.text)))?;
+    };
+
+    let rdata_tokens: Vec<Token> = tokens.copied().collect();
+    let rdata = parse_rdata(rtype, &rdata_tokens, origin)?;
+
+    Ok(Record {
+        name,
+        rtype,
+        rclass: rclass.unwrap_or(RecordClass::IN),
+        ttl: ttl.unwrap_or(default_ttl),
+        rdata,
+    })
+}
+
+/// Tokenizes a line, dropping the parentheses that group multi-line entries.
+fn tokenize_line(line: &str) -> Result<Vec<Token<'_>>, ParseError> {
+    Ok(tokenize(line)?
+        .into_iter()
+        .filter(|t| t.quoted || (t.text != "(" && t.text != ")"))
+        .collect())
+}
+
+/// Parses a single master file line. Names must be fully qualified, since
+/// there is no origin, and a missing TTL is taken as 0.
+impl FromStr for Record {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_record(&tokenize_line(s)?, None, None, 0)
+    }
+}
+
+/// Parses `<name> [<class>] <type>`, the form used in the question section of
+/// dig output, which may be preceded by a `;`.
+impl FromStr for Question {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim_start();
+        let tokens = tokenize_line(s.strip_prefix(';').unwrap_or(s))?;
+        let invalid = || ParseError::Invalid(format!("Invalid question: {}", s));
+        let (qname, qclass, qtype) = match tokens.as_slice() {
+            [name, rtype] => (name, None, rtype),
+            [name, class, rtype] => (name, Some(class), rtype),
+            _ => return Err(invalid()),
+        };
+        Ok(Question {
+            qname: Name::parse(qname.text, None)?,
+            qclass: match qclass {
+                Some(class) => class.text.parse().map_err(|_| invalid())?,
+                None => RecordClass::IN,
+            },
+            qtype: qtype.text.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// Parses the presentation format RDATA of a record of type `rtype`.
+/// Relative names are completed with `origin`.
+pub fn parse_rdata(
+    rtype: RecordType,
+    tokens: &[Token],
+    origin: Option<&Name>,
+) -> Result<RData, ParseError> {
+    let name = |text: &str| Name::parse(text, origin);
+    if let Some((first, rest)) = tokens.split_first() {
+        if !first.quoted && first.text == "\\#" {
+            return parse_generic_rdata(rtype, rest);
+        }
+    }
+
+    let mut fields = Fields {
+        tokens: tokens.iter(),
+        rtype,
+    };
+    let rdata = match rtype {
+        RecordType::A => RData::A(fields.parse("address")?),
+        RecordType::AAAA => RData::AAAA(fields.parse("address")?),
+        RecordType::CNAME => RData::CNAME(name(fields.text("target")?)?),
+        RecordType::MX => RData::MX {
+            preference: fields.parse("preference")?,
+            exchange: name(fields.text("exchange")?)?,
+        },
+        RecordType::NS => RData::NS(name(fields.text("nsdname")?)?),
+        RecordType::SOA => RData::SOA {
+            mname: name(fields.text("mname")?)?,
+            rname: name(fields.text("rname")?)?,
+            serial: fields.parse("serial")?,
+            refresh: fields.ttl("refresh")?,
+            retry: fields.ttl("retry")?,
+            expire: fields.ttl("expire")?,
+            minimum: fields.ttl("minimum")?,
+        },
+        RecordType::TXT => {
+            let mut strings = Vec::new();
+            let first = fields.text("text")?;
+            // Strings longer than 255 bytes are split rather than rejected.
+            for text in std::iter::once(first).chain(fields.by_ref().map(|t| t.text)) {
+                let data = unescape(text)?;
+                if data.is_empty() {
+                    strings.push(data);
+                } else {
+                    strings.extend(data.chunks(255).map(<[u8]>::to_vec));
+                }
+            }
+            RData::TXT(strings)
+        }
+        RecordType::PTR => RData::PTR(name(fields.text("ptrdname")?)?),
+        RecordType::SRV => RData::SRV {
+            priority: fields.parse("priority")?,
+            weight: fields.parse("weight")?,
+            port: fields.parse("port")?,
+            target: name(fields.text("target")?)?,
+        },
+        RecordType::NAPTR => RData::NAPTR {
+            order: fields.parse("order")?,
+            preference: fields.parse("preference")?,
+            flags: fields.character_string("flags")?,
+            services: fields.character_string("services")?,
+            regexp: fields.character_string("regexp")?,
+            replacement: name(fields.text("replacement")?)?,
+        },
+        RecordType::URI => RData::URI {
+            priority: fields.parse("priority")?,
+            weight: fields.parse("weight")?,
+            target: String::from_utf8(unescape(fields.text("target")?)?)
+                .map_err(|_| ParseError::Invalid("URI target is not UTF-8".to_string()))?,
+        },
+        RecordType::CAA => {
+            let flags = fields.parse("flags")?;
+            let tag = fields.text("tag")?;
+            if !Caa::is_valid_tag(tag) {
+                return Err(ParseError::Invalid(format!(
+                    "Invalid tag in CAA record: {}",
+                    tag
+                )));
+            }
+            RData::CAA(Caa {
+                flags,
+                tag: tag.to_string(),
+                value: unescape(fields.text("value")?)?,
+            })
+        }
+        RecordType::TLSA | RecordType::SMIMEA => {
+            let tlsa = Tlsa {
+                cert_usage: fields.parse("certificate usage")?,
+                selector: fields.parse("selector")?,
+                matching_type: fields.parse("matching type")?,
+                cert_data: fields.hex("certificate association data")?,
+            };
+            if rtype == RecordType::TLSA {
+                RData::TLSA(tlsa)
+            } else {
+                RData::SMIMEA(tlsa)
+            }
+        }
+        RecordType::SSHFP => RData::SSHFP(Sshfp {
+            algorithm: fields.parse("algorithm")?,
+            fingerprint_type: fields.parse("fingerprint type")?,
+            fingerprint: fields.hex("fingerprint")?,
+        }),
+        RecordType::OPENPGPKEY => RData::OPENPGPKEY(fields.base64("public key")?),
+        RecordType::SVCB | RecordType::HTTPS => {
+            let mut svcb = Svcb {
+                priority: fields.parse("priority")?,
+                target: name(fields.text("target")?)?,
+                params: Vec::new(),
+            };
+            while let Some(token) = fields.next() {
+                let (key, value) = match token.text.split_once('=') {
+                    // `key="value"` is split into `key=` and a quoted token.
+                    Some((key, "")) if fields.peek_quoted() => {
+                        (key, Some(unescape(fields.text(key)?)?))
+                    }
+                    Some((key, value)) => (key, Some(unescape(value)?)),
+                    None => (token.text, None),
+                };
+                svcb.params
+                    .push(SvcParam::from_presentation(key, value.as_deref())?);
+            }
+            svcb.sort_params()?;
+            svcb.validate()?;
+            if rtype == RecordType::SVCB {
+                RData::SVCB(svcb)
+            } else {
+                RData::HTTPS(svcb)
+            }
+        }
+        RecordType::DNSKEY | RecordType::CDNSKEY => {
+            let key = Dnskey {
+                flags: fields.parse("flags")?,
+                protocol: fields.parse("protocol")?,
+                algorithm: fields.parse("algorithm")?,
+                public_key: fields.base64("public key")?,
+            };
+            if rtype == RecordType::DNSKEY {
+                RData::DNSKEY(key)
+            } else {
+                RData::CDNSKEY(key)
+            }
+        }
+        RecordType::DS | RecordType::CDS => {
+            let ds = Ds {
+                key_tag: fields.parse("key tag")?,
+                algorithm: fields.parse("algorithm")?,
+                digest_type: fields.parse("digest type")?,
+                digest: fields.hex("digest")?,
+            };
+            if rtype == RecordType::DS {
+                RData::DS(ds)
+            } else {
+                RData::CDS(ds)
+            }
+        }
+        RecordType::RRSIG => RData::RRSIG(Rrsig {
+            type_covered: fields.parse("type covered")?,
+            algorithm: fields.parse("algorithm")?,
+            labels: fields.parse("labels")?,
+            original_ttl: fields.parse("original TTL")?,
+            expiration: fields.timestamp("expiration")?,
+            inception: fields.timestamp("inception")?,
+            key_tag: fields.parse("key tag")?,
+            signer_name: name(fields.text("signer name")?)?,
+            signature: fields.base64("signature")?,
+        }),
+        RecordType::NSEC => RData::NSEC(Nsec {
+            next_domain: name(fields.text("next domain name")?)?,
+            types: fields.types()?,
+        }),
+        RecordType::NSEC3 => RData::NSEC3(Nsec3 {
+            hash_algorithm: fields.parse("hash algorithm")?,
+            flags: fields.parse("flags")?,
+            iterations: fields.parse("iterations")?,
+            salt: fields.salt()?,
+            next_hashed_owner: {
+                let text = fields.text("next hashed owner name")?;
+                base32hex_decode(text)
+                    .map_err(|e| ParseError::Invalid(format!("{} in NSEC3 record: {}", e, text)))?
+            },
+            types: fields.types()?,
+        }),
+        RecordType::NSEC3PARAM => RData::NSEC3PARAM(Nsec3Param {
+            hash_algorithm: fields.parse("hash algorithm")?,
+            flags: fields.parse("flags")?,
+            iterations: fields.parse("iterations")?,
+            salt: fields.salt()?,
+        }),
+        RecordType::DNAME => RData::DNAME(name(fields.text("target")?)?),
+        RecordType::HINFO => RData::HINFO {
+            cpu: fields.character_string("cpu")?,
+            os: fields.character_string("os")?,
+        },
+        RecordType::RP => RData::RP {
+            mbox: name(fields.text("mbox")?)?,
+            txt: name(fields.text("txt")?)?,
+        },
+        RecordType::LOC => {
+            let text: Vec<&str> = fields.by_ref().map(|t| t.text).collect();
+            RData::LOC(
+                text.join(" ")
+                    .parse()
+                    .map_err(|e: LocError| ParseError::Invalid(e.to_string()))?,
+            )
+        }
+        RecordType::AFSDB => RData::AFSDB {
+            subtype: fields.parse("subtype")?,
+            hostname: name(fields.text("hostname")?)?,
+        },
+        RecordType::CERT => RData::CERT(Cert {
+            cert_type: {
+                let text = fields.text("type")?;
+                Cert::parse_type(text).ok_or_else(|| {
+                    ParseError::Invalid(format!("Invalid type in CERT record: {}", text))
+                })?
+            },
+            key_tag: fields.parse("key tag")?,
+            algorithm: fields.parse("algorithm")?,
+            certificate: fields.base64("certificate")?,
+        }),
+        RecordType::ZONEMD => RData::ZONEMD(Zonemd {
+            serial: fields.parse("serial")?,
+            scheme: fields.parse("scheme")?,
+            hash_algorithm: fields.parse("hash algorithm")?,
+            digest: fields.hex("digest")?,
+        }),
+        RecordType::CSYNC => RData::CSYNC(Csync {
+            serial: fields.parse("serial")?,
+            flags: fields.parse("flags")?,
+            types: fields.types()?,
+        }),
+        _ => {
+            return Err(ParseError::Invalid(format!(
+                "{} record requires generic \\# RDATA",
+                rtype
+            )))
+        }
+    };
+    fields.finish()?;
+    Ok(rdata)
+}
+
+/// Parses a TTL given in seconds or, as BIND allows, with `w`, `d`, `h`,
+/// `m` and `s` units such as `1h30m`.
+pub fn parse_ttl(text: &str) -> Option<u32> {
+    if text.bytes().all(|b| b.is_ascii_digit()) {
+        return text.parse().ok();
+    }
+    let mut total: u32 = 0;
+    let mut digits = 0;
+    let mut value: u32 = 0;
+    for b in text.bytes() {
+        let unit = match b.to_ascii_lowercase() {
+            b'0'..=b'9' => {
+                value = value.checked_mul(10)?.checked_add((b - b'0') as u32)?;
+                digits += 1;
+                continue;
+            }
+            b'w' => 604_800,
+            b'd' => 86_400,
+            b'h' => 3_600,
+            b'm' => 60,
+            b's' => 1,
+            _ => return None,
+        };
+        if digits == 0 {
+            return None;
+        }
+        total = total.checked_add(value.checked_mul(unit)?)?;
+        value = 0;
+        digits = 0;
+    }
+    // Every number must carry a unit once units are used.
+    if digits != 0 {
+        return None;
+    }
+    Some(total)
+}
+
+/// Parses RDATA in the generic `\# <length> <hex>` form of RFC 3597. The
+/// data of known types is decoded as if it had been read off the wire.
+fn parse_generic_rdata(rtype: RecordType, tokens: &[Token]) -> Result<RData, ParseError> {
+    let invalid = |reason: &str| {
+        ParseError::Invalid(format!("Invalid generic RDATA for {}: {}", rtype, reason))
+    };
+    let (length, hex) = tokens
+        .split_first()
+        .ok_or_else(|| invalid("missing length"))?;
+    let length: u16 = length.text.parse().map_err(|_| invalid(length.text))?;
+    let hex: String = hex.iter().map(|t| t.text).collect();
+    let data = hex_decode(&hex).map_err(|e| invalid(&e.to_string()))?;
+    if data.len() != length as usize {
+        return Err(invalid("length does not match data"));
+    }
+
+    let mut reader = Cursor::new(&data[..]);
+    let rdata = RData::read(&mut reader, rtype, length).map_err(|e| invalid(&e.to_string()))?;
+    if reader.position() != length as u64 {
+        return Err(invalid("length does not match data"));
+    }
+    Ok(rdata)
+}
+
+/// The RDATA fields of a record, consumed in order.
+struct Fields<'a> {
+    tokens: std::slice::Iter<'a, Token<'a>>,
+    rtype: RecordType,
+}
+
+impl<'a> Fields<'a> {
+    fn text(&mut self, field: &str) -> Result<&'a str, ParseError> {
+        self.tokens
+            .next()
+            .map(|t| t.text)
+            .ok_or_else(|| ParseError::Invalid(format!("{} record missing {}", self.rtype, field)))
+    }
+
+    fn parse<T: FromStr>(&mut self, field: &str) -> Result<T, ParseError> {
+        let text = self.text(field)?;
+        text.parse().map_err(|_| {
+            ParseError::Invalid(format!(
+                "Invalid {} in {} record: {}",
+                field, self.rtype, text
+            ))
+        })
+    }
+
+    fn character_string(&mut self, field: &str) -> Result<Vec<u8>, ParseError> {
+        let data = unescape(self.text(field)?)?;
+        if data.len() > 255 {
+            return Err(ParseError::Invalid(format!(
+                "{} in {} record longer than 255 bytes",
+                field, self.rtype
+            )));
+        }
+        Ok(data)
+    }
+
+    fn ttl(&mut self, field: &str) -> Result<u32, ParseError> {
+        let text = self.text(field)?;
+        parse_ttl(text).ok_or_else(|| {
+            ParseError::Invalid(format!(
+                "Invalid {} in {} record: {}",
+                field, self.rtype, text
+            ))
+        })
+    }
+
+    fn timestamp(&mut self, field: &str) -> Result<u32, ParseError> {
+        let text = self.text(field)?;
+        parse_timestamp(text).ok_or_else(|| {
+            ParseError::Invalid(format!(
+                "Invalid {} in {} record: {}",
+                field, self.rtype, text
+            ))
+        })
+    }
+
+    /// An NSEC3 salt in hex, or `-` for an empty salt.
+    fn salt(&mut self) -> Result<Vec<u8>, ParseError> {
+        match self.text("salt")? {
+            "-" => Ok(Vec::new()),
+            text => {
+                let salt = hex_decode(text).map_err(|e| {
+                    ParseError::Invalid(format!("{} in {} salt: {}", e, self.rtype, text))
+                })?;
+                if salt.len() > 255 {
+                    return Err(ParseError::Invalid(format!(
+                        "{} salt longer than 255 bytes",
+                        self.rtype
+                    )));
+                }
+                Ok(salt)
+            }
+        }
+    }
+
+    /// The remaining tokens as a type bitmap.
+    fn types(&mut self) -> Result<TypeBitmap, ParseError> {
+        let rtype = self.rtype;
+        self.tokens
+            .by_ref()
+            .map(|token| {
+                token.text.parse::<RecordType>().map_err(|_| {
+                    ParseError::Invalid(format!("Unknown type in {} bitmap: {}", rtype, token.text))
+                })
+            })
+            .collect()
+    }
+
+    fn peek_quoted(&self) -> bool {
+        self.tokens.as_slice().first().is_some_and(|t| t.quoted)
+    }
+
+    /// The remaining tokens concatenated, as used for hex and base64 fields
+    /// that may be split by whitespace.
+    fn rest(&mut self, field: &str) -> Result<String, ParseError> {
+        let text: String = self.tokens.by_ref().map(|t| t.text).collect();
+        if text.is_empty() {
+            return Err(ParseError::Invalid(format!(
+                "{} record missing {}",
+                self.rtype, field
+            )));
+        }
+        Ok(text)
+    }
+
+    fn hex(&mut self, field: &str) -> Result<Vec<u8>, ParseError> {
+        let text = self.rest(field)?;
+        hex_decode(&text)
+            .map_err(|e| ParseError::Invalid(format!("{} in {} record: {}", e, self.rtype, text)))
+    }
+
+    fn base64(&mut self, field: &str) -> Result<Vec<u8>, ParseError> {
+        let text = self.rest(field)?;
+        base64_decode(&text)
+            .map_err(|e| ParseError::Invalid(format!("{} in {} record: {}", e, self.rtype, text)))
+    }
+
+    fn finish(mut self) -> Result<(), ParseError> {
+        match self.tokens.next() {
+            Some(token) => Err(ParseError::Invalid(format!(
+                "Unexpected data in {} record: {}",
+                self.rtype, token.text
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<'a> Iterator for Fields<'a> {
+    type Item = &'a Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.tokens.next()
+    }
+}
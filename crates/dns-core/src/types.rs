@@ -0,0 +1,248 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("Unknown mnemonic: {0}")]
+pub struct UnknownMnemonic(pub String);
+
+/// Defines a numeric DNS parameter enum with an `Unknown` fallback, integer
+/// conversions, and mnemonic `Display`/`FromStr`. Values without a mnemonic
+/// are written and parsed as `<generic><value>`, e.g. `TYPE1234` (RFC 3597).
+macro_rules! dns_enum {
+    (
+        $(#[$meta:meta])*
+        $name:ident($int:ty), $generic:literal {
+            $($variant:ident = $value:literal => $mnemonic:literal,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy)]
+        pub enum $name {
+            $($variant,)*
+            Unknown($int),
+        }
+
+        impl $name {
+            pub fn mnemonic(&self) -> Option<&'static str> {
+                match self {
+                    $($name::$variant => Some($mnemonic),)*
+                    $name::Unknown(value) => $name::from(*value).known_mnemonic(),
+                }
+            }
+
+            fn known_mnemonic(&self) -> Option<&'static str> {
+                match self {
+                    $($name::$variant => Some($mnemonic),)*
+                    $name::Unknown(_) => None,
+                }
+            }
+        }
+
+        impl From<$int> for $name {
+            fn from(value: $int) -> Self {
+                match value {
+                    $($value => $name::$variant,)*
+                    value => $name::Unknown(value),
+                }
+            }
+        }
+
+        impl From<$name> for $int {
+            fn from(value: $name) -> Self {
+                match value {
+                    $($name::$variant => $value,)*
+                    $name::Unknown(value) => value,
+                }
+            }
+        }
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                <$int>::from(*self) == <$int>::from(*other)
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl Hash for $name {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                <$int>::from(*self).hash(state)
+            }
+        }
+
+        impl PartialOrd for $name {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $name {
+            fn cmp(&self, other: &Self) -> Ordering {
+                <$int>::from(*self).cmp(&<$int>::from(*other))
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self.mnemonic() {
+                    Some(mnemonic) => f.write_str(mnemonic),
+                    None => write!(f, "{}{}", $generic, <$int>::from(*self)),
+                }
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = UnknownMnemonic;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let upper = s.to_ascii_uppercase();
+                match upper.as_str() {
+                    $($mnemonic => Ok($name::$variant),)*
+                    _ => upper
+                        .strip_prefix($generic)
+                        .filter(|digits| digits.bytes().all(|b| b.is_ascii_digit()))
+                        .and_then(|digits| digits.parse::<$int>().ok())
+                        .map($name::from)
+                        .ok_or_else(|| UnknownMnemonic(s.to_string())),
+                }
+            }
+        }
+    };
+}
+
+dns_enum! {
+    /// Resource record TYPE and query QTYPE values.
+    RecordType(u16), "TYPE" {
+        A = 1 => "A",
+        NS = 2 => "NS",
+        CNAME = 5 => "CNAME",
+        SOA = 6 => "SOA",
+        PTR = 12 => "PTR",
+        HINFO = 13 => "HINFO",
+        MX = 15 => "MX",
+        TXT = 16 => "TXT",
+        RP = 17 => "RP",
+        AFSDB = 18 => "AFSDB",
+        SIG = 24 => "SIG",
+        KEY = 25 => "KEY",
+        AAAA = 28 => "AAAA",
+        LOC = 29 => "LOC",
+        SRV = 33 => "SRV",
+        NAPTR = 35 => "NAPTR",
+        CERT = 37 => "CERT",
+        DNAME = 39 => "DNAME",
+        OPT = 41 => "OPT",
+        DS = 43 => "DS",
+        SSHFP = 44 => "SSHFP",
+        RRSIG = 46 => "RRSIG",
+        NSEC = 47 => "NSEC",
+        DNSKEY = 48 => "DNSKEY",
+        NSEC3 = 50 => "NSEC3",
+        NSEC3PARAM = 51 => "NSEC3PARAM",
+        TLSA = 52 => "TLSA",
+        SMIMEA = 53 => "SMIMEA",
+        CDS = 59 => "CDS",
+        CDNSKEY = 60 => "CDNSKEY",
+        OPENPGPKEY = 61 => "OPENPGPKEY",
+        CSYNC = 62 => "CSYNC",
+        ZONEMD = 63 => "ZONEMD",
+        SVCB = 64 => "SVCB",
+        HTTPS = 65 => "HTTPS",
+        TKEY = 249 => "TKEY",
+        TSIG = 250 => "TSIG",
+        IXFR = 251 => "IXFR",
+        AXFR = 252 => "AXFR",
+        ANY = 255 => "ANY",
+        URI = 256 => "URI",
+        CAA = 257 => "CAA",
+    }
+}
+
+dns_enum! {
+    /// Resource record CLASS and query QCLASS values.
+    RecordClass(u16), "CLASS" {
+        IN = 1 => "IN",
+        CH = 3 => "CH",
+        HS = 4 => "HS",
+        NONE = 254 => "NONE",
+        ANY = 255 => "ANY",
+    }
+}
+
+dns_enum! {
+    /// Header OPCODE values.
+    Opcode(u8), "OPCODE" {
+        Query = 0 => "QUERY",
+        IQuery = 1 => "IQUERY",
+        Status = 2 => "STATUS",
+        Notify = 4 => "NOTIFY",
+        Update = 5 => "UPDATE",
+        Dso = 6 => "DSO",
+    }
+}
+
+dns_enum! {
+    /// Response codes, including the 12-bit extended values that combine the
+    /// header RCODE with the upper bits carried in the EDNS0 OPT record.
+    Rcode(u16), "RCODE" {
+        NoError = 0 => "NOERROR",
+        FormErr = 1 => "FORMERR",
+        ServFail = 2 => "SERVFAIL",
+        NXDomain = 3 => "NXDOMAIN",
+        NotImp = 4 => "NOTIMP",
+        Refused = 5 => "REFUSED",
+        YXDomain = 6 => "YXDOMAIN",
+        YXRRSet = 7 => "YXRRSET",
+        NXRRSet = 8 => "NXRRSET",
+        NotAuth = 9 => "NOTAUTH",
+        NotZone = 10 => "NOTZONE",
+        DsoTypeNI = 11 => "DSOTYPENI",
+        BadVers = 16 => "BADVERS",
+        BadKey = 17 => "BADKEY",
+        BadTime = 18 => "BADTIME",
+        BadMode = 19 => "BADMODE",
+        BadName = 20 => "BADNAME",
+        BadAlg = 21 => "BADALG",
+        BadTrunc = 22 => "BADTRUNC",
+        BadCookie = 23 => "BADCOOKIE",
+    }
+}
+
+impl RecordType {
+    /// Meta and query-only types that never appear as stored RRsets.
+    pub fn is_meta(&self) -> bool {
+        matches!(
+            self,
+            RecordType::OPT
+                | RecordType::TKEY
+                | RecordType::TSIG
+                | RecordType::IXFR
+                | RecordType::AXFR
+                | RecordType::ANY
+        )
+    }
+}
+
+impl Rcode {
+    /// Combines the 4-bit header RCODE with the 8-bit EDNS0 extended RCODE.
+    pub fn from_parts(header_rcode: u8, ext_rcode: u8) -> Self {
+        Rcode::from(((ext_rcode as u16) << 4) | (header_rcode & 0x0F) as u16)
+    }
+
+    /// The lower four bits, carried in the message header.
+    pub fn header_bits(&self) -> u8 {
+        (u16::from(*self) & 0x0F) as u8
+    }
+
+    /// The upper eight bits, carried in the OPT record TTL.
+    pub fn ext_bits(&self) -> u8 {
+        ((u16::from(*self) >> 4) & 0xFF) as u8
+    }
+
+    pub fn is_extended(&self) -> bool {
+        u16::from(*self) > 0x0F
+    }
+}
@@ -0,0 +1,63 @@
+use crate::error::DecodeError;
+use std::io::{self, Cursor, Write};
+
+pub(crate) fn read_slice<'a>(
+    reader: &mut Cursor<&'a [u8]>,
+    len: usize,
+) -> Result<&'a [u8], DecodeError> {
+    let buf: &'a [u8] = reader.get_ref();
+    let start = reader.position() as usize;
+    let slice = start
+        .checked_add(len)
+        .and_then(|end| buf.get(start..end))
+        .ok_or(DecodeError::Truncated { offset: start })?;
+    reader.set_position((start + len) as u64);
+    Ok(slice)
+}
+
+pub(crate) fn read_u8(reader: &mut Cursor<&[u8]>) -> Result<u8, DecodeError> {
+    Ok(read_slice(reader, 1)?[0])
+}
+
+pub(crate) fn read_u16(reader: &mut Cursor<&[u8]>) -> Result<u16, DecodeError> {
+    let bytes = read_slice(reader, 2)?;
+    Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+pub(crate) fn read_u32(reader: &mut Cursor<&[u8]>) -> Result<u32, DecodeError> {
+    let bytes = read_slice(reader, 4)?;
+    Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+pub(crate) fn u16_at(buf: &[u8], offset: usize) -> Result<u16, DecodeError> {
+    match buf.get(offset..offset + 2) {
+        Some(bytes) => Ok(u16::from_be_bytes([bytes[0], bytes[1]])),
+        None => Err(DecodeError::Truncated { offset }),
+    }
+}
+
+pub(crate) fn u32_at(buf: &[u8], offset: usize) -> Result<u32, DecodeError> {
+    match buf.get(offset..offset + 4) {
+        Some(bytes) => Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])),
+        None => Err(DecodeError::Truncated { offset }),
+    }
+}
+
+/// Reads a length-prefixed <character-string>.
+pub(crate) fn read_character_string<'a>(
+    reader: &mut Cursor<&'a [u8]>,
+) -> Result<&'a [u8], DecodeError> {
+    let len = read_u8(reader)?;
+    read_slice(reader, len as usize)
+}
+
+pub(crate) fn write_character_string<W: Write>(writer: &mut W, data: &[u8]) -> io::Result<()> {
+    let len = u8::try_from(data.len()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Character string longer than 255 bytes",
+        )
+    })?;
+    writer.write_all(&[len])?;
+    writer.write_all(data)
+}
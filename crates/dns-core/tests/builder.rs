@@ -0,0 +1,82 @@
+use dns_core::{Edns, Message, Name, Opcode, RData, Rcode, Record, RecordClass, RecordType};
+use std::net::Ipv4Addr;
+
+fn a_record(name: &Name, octet: u8) -> Record {
+    Record {
+        name: name.clone(),
+        rtype: RecordType::A,
+        rclass: RecordClass::IN,
+        ttl: 300,
+        rdata: RData::A(Ipv4Addr::new(192, 0, 2, octet)),
+    }
+}
+
+#[test]
+fn query_and_response() {
+    let name = Name::parse("example.com.", None).unwrap();
+    let mut query = Message::query(name.clone(), RecordType::A)
+        .edns(Edns::new())
+        .build();
+    assert!(query.header.rd);
+    assert!(!query.header.qr);
+    assert_eq!(query.header.qdcount, 1);
+    assert_eq!(query.header.arcount, 1);
+    query.header.cd = true;
+
+    let response = Message::response_to(&query)
+        .aa(true)
+        .answer(a_record(&name, 1))
+        .answer(a_record(&name, 2))
+        .authority(Record {
+            name: name.clone(),
+            rtype: RecordType::NS,
+            rclass: RecordClass::IN,
+            ttl: 300,
+            rdata: RData::NS(Name::parse("ns.example.com.", None).unwrap()),
+        })
+        .build();
+    assert_eq!(response.header.id, query.header.id);
+    assert_eq!(response.header.opcode, Opcode::Query);
+    assert!(response.header.qr && response.header.aa && response.header.rd);
+    assert!(response.header.cd && !response.header.ad);
+    assert_eq!(response.questions, query.questions);
+    assert_eq!(
+        (
+            response.header.ancount,
+            response.header.nscount,
+            response.header.arcount
+        ),
+        (2, 1, 0)
+    );
+
+    let decoded = Message::from_bytes(&response.to_bytes().unwrap()).unwrap();
+    assert_eq!(decoded, response);
+}
+
+#[test]
+fn extended_rcode_uses_opt() {
+    let request = Message::query(Name::root(), RecordType::NS).build();
+    let response = Message::response_to(&request)
+        .rcode(Rcode::BadCookie)
+        .edns(Edns::new())
+        .additional(Edns::new().to_record())
+        .build();
+    assert_eq!(response.additionals.len(), 1);
+    assert_eq!(response.rcode(), Rcode::BadCookie);
+    let decoded = Message::from_bytes(&response.to_bytes().unwrap()).unwrap();
+    assert_eq!(decoded.rcode(), Rcode::BadCookie);
+}
+
+#[test]
+fn write_derives_counts() {
+    let name = Name::parse("example.com.", None).unwrap();
+    let mut message = Message::new();
+    message.answers.push(a_record(&name, 1));
+    message.header.ancount = 7;
+    message.header.arcount = 3;
+
+    let bytes = message.to_bytes().unwrap();
+    assert_eq!(&bytes[4..12], &[0, 0, 0, 1, 0, 0, 0, 0]);
+    let decoded = Message::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded.answers, message.answers);
+}
@@ -0,0 +1,56 @@
+use dns_core::{Flags, Header, Opcode, Rcode};
+use std::io::Cursor;
+
+#[test]
+fn header_roundtrips_every_bit() {
+    for bit in 0..16 {
+        let mut bytes = [0x12, 0x34, 0, 0, 0, 1, 0, 2, 0, 3, 0, 4];
+        let word = (1u16 << bit).to_be_bytes();
+        bytes[2..4].copy_from_slice(&word);
+
+        let header = Header::read(&mut Cursor::new(&bytes[..])).unwrap();
+        let mut written = Vec::new();
+        header.write(&mut written).unwrap();
+        assert_eq!(written, bytes, "bit {}", bit);
+    }
+}
+
+#[test]
+fn ad_and_cd_bits() {
+    let bytes = [0, 0, 0x81, 0xB0, 0, 0, 0, 0, 0, 0, 0, 0];
+    let header = Header::read(&mut Cursor::new(&bytes[..])).unwrap();
+    assert!(header.qr && header.rd && header.ra && header.ad && header.cd);
+    assert!(!header.z);
+    assert_eq!(header.flags(), Flags::from_bits(0x81B0));
+    assert_eq!(header.flags().to_string(), "qr rd ra ad cd");
+}
+
+#[test]
+fn flags_set_header_bits() {
+    let mut header = Header::new();
+    header.opcode = Opcode::Notify;
+    header.rcode = Rcode::Refused;
+    header.set_flags(Flags::QR | Flags::AA | Flags::Z | Flags::AD);
+    assert!(header.qr && header.aa && header.z && header.ad);
+    assert!(!header.rd && !header.cd);
+
+    let mut written = Vec::new();
+    header.write(&mut written).unwrap();
+    assert_eq!(&written[2..4], &[0xA4, 0x65]);
+
+    let mut flags = header.flags();
+    flags.remove(Flags::Z);
+    assert_eq!(flags.to_string(), "qr aa ad");
+    assert!(Flags::empty().is_empty());
+    assert_eq!(Flags::from_bits(0xFFFF).bits(), 0x87F0);
+}
+
+#[test]
+fn reject_opcode_wider_than_four_bits() {
+    let mut header = Header::new();
+    header.opcode = Opcode::Unknown(16);
+    let mut written = Vec::new();
+    let err = header.write(&mut written).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(written.is_empty());
+}
@@ -0,0 +1,113 @@
+#![cfg(feature = "serde")]
+
+use dns_core::{Edns, JsonError, Message, Name, RData, Record, RecordType};
+use serde_json::json;
+
+fn response() -> Message {
+    let query = Message::query("example.com.".parse().unwrap(), RecordType::MX)
+        .id(0x1234)
+        .build();
+    Message::response_to(&query)
+        .aa(true)
+        .answer(
+            "example.com. 300 IN MX 10 mail.example.com."
+                .parse()
+                .unwrap(),
+        )
+        .answer("example.com. 300 IN TYPE65280 \\# 2 ABCD".parse().unwrap())
+        .edns(Edns::new())
+        .build()
+}
+
+#[test]
+fn serde_roundtrip() {
+    let message = response();
+    let text = serde_json::to_string(&message).unwrap();
+    assert!(text.contains("\"qname\":\"example.com.\""));
+    assert!(text.contains("\"rtype\":\"MX\""));
+    let decoded: Message = serde_json::from_str(&text).unwrap();
+    assert_eq!(decoded, message);
+
+    let record: Record = serde_json::from_value(json!({
+        "name": "www.example.com.",
+        "rtype": "AAAA",
+        "rclass": "IN",
+        "ttl": 60,
+        "rdata": {"AAAA": "2001:db8::1"}
+    }))
+    .unwrap();
+    assert_eq!(record.rdata, RData::AAAA("2001:db8::1".parse().unwrap()));
+    assert!(serde_json::from_value::<Name>(json!("a..b")).is_err());
+}
+
+#[test]
+fn rfc8427_encoding() {
+    let value = response().to_json();
+    assert_eq!(value["ID"], 0x1234);
+    assert_eq!(value["QR"], true);
+    assert_eq!(value["AA"], true);
+    assert_eq!(value["QNAME"], "example.com.");
+    assert_eq!(value["QTYPE"], 15);
+    assert_eq!(value["QTYPEname"], "MX");
+    assert_eq!(value["ANCOUNT"], 2);
+    assert_eq!(
+        value["answerRRs"][0],
+        json!({
+            "NAME": "example.com.",
+            "TYPE": 15,
+            "TYPEname": "MX",
+            "CLASS": 1,
+            "CLASSname": "IN",
+            "TTL": 300,
+            "rdataMX": "10 mail.example.com."
+        })
+    );
+    assert_eq!(value["answerRRs"][1]["RDATAHEX"], "ABCD");
+    assert_eq!(value["additionalRRs"][0]["CLASS"], 1232);
+    assert!(value.get("messageOctetsHEX").is_none());
+}
+
+#[test]
+fn rfc8427_roundtrip() {
+    let message = response();
+    assert_eq!(Message::from_json(&message.to_json()).unwrap(), message);
+
+    let value = message.to_json_with_octets().unwrap();
+    let hex = value["messageOctetsHEX"].as_str().unwrap();
+    assert!(hex.starts_with("1234"));
+    assert_eq!(Message::from_json(&value).unwrap(), message);
+}
+
+#[test]
+fn rfc8427_decoding() {
+    // The query example of RFC 8427 section 7.1, with the counts left out.
+    let message = Message::from_json(&json!({
+        "ID": 19678, "QR": 0, "Opcode": 0,
+        "AA": 0, "TC": 0, "RD": 0, "RA": 0, "AD": 0, "CD": 0, "RCODE": 0,
+        "QNAME": "example.com", "QTYPE": 1, "QCLASS": 1
+    }))
+    .unwrap();
+    assert_eq!(message.header.id, 19678);
+    assert_eq!(message.header.qdcount, 1);
+    assert_eq!(message.questions[0].qtype, RecordType::A);
+
+    let message = Message::from_json(&json!({
+        "questionRRs": [{"NAME": "a.example.", "TYPEname": "TXT"}],
+        "answerRRs": [{"NAME": "a.example.", "TYPEname": "TXT", "TTL": 5,
+                       "rdataTXT": "\"hello\" \"world\""}]
+    }))
+    .unwrap();
+    assert_eq!(
+        message.answers[0].rdata,
+        RData::TXT(vec![b"hello".to_vec(), b"world".to_vec()])
+    );
+
+    assert!(matches!(
+        Message::from_json(&json!({"QNAME": "example.com."})),
+        Err(JsonError::MissingMember(_))
+    ));
+    assert!(matches!(
+        Message::from_json(&json!({"ID": 70000})),
+        Err(JsonError::InvalidMember(_))
+    ));
+}
@@ -0,0 +1,132 @@
+use dns_core::{
+    Edns, EdnsOption, Header, Message, Name, ParseError, Question, RData, Rcode, Record,
+    RecordClass, RecordType,
+};
+use std::net::Ipv4Addr;
+
+#[test]
+fn record_display_roundtrip() {
+    let lines = [
+        "example.com.\t3600\tIN\tA\t192.0.2.1",
+        "example.com.\t3600\tIN\tMX\t10 mail.example.com.",
+        "example.com.\t300\tIN\tTXT\t\"v=spf1 -all\" \"a \\\"quoted\\\" string\"",
+        "example.com.\t300\tIN\tHTTPS\t1 . alpn=\"h2,h3\" port=8443",
+        "example.com.\t300\tIN\tCAA\t0 issue \"ca.example.net\"",
+        "example.com.\t300\tCH\tTYPE65280\t\\# 3 ABCDEF",
+    ];
+    for line in lines {
+        let record: Record = line.parse().unwrap();
+        assert_eq!(record.to_string(), line);
+    }
+}
+
+#[test]
+fn record_from_str_defaults() {
+    let record: Record = "www.example.com. A 192.0.2.1".parse().unwrap();
+    assert_eq!(record.name, Name::parse("www.example.com.", None).unwrap());
+    assert_eq!(record.rclass, RecordClass::IN);
+    assert_eq!(record.ttl, 0);
+    assert_eq!(record.rdata, RData::A(Ipv4Addr::new(192, 0, 2, 1)));
+
+    let record: Record = "example.com. IN 1h SOA ns1.example.com. hostmaster.example.com. (\
+                          2024010101 1d 2h 4w 1h )"
+        .parse()
+        .unwrap();
+    assert_eq!(record.ttl, 3600);
+    assert!(matches!(record.rdata, RData::SOA { refresh: 86400, .. }));
+
+    assert!(matches!(
+        "example.com. 300 IN A".parse::<Record>(),
+        Err(ParseError::Invalid(_))
+    ));
+    assert!(matches!(
+        "example..com. 300 IN A 192.0.2.1".parse::<Record>(),
+        Err(ParseError::Name(_))
+    ));
+}
+
+#[test]
+fn question_from_str() {
+    let question: Question = ";example.com.\t\tIN\tAAAA".parse().unwrap();
+    assert_eq!(question.qtype, RecordType::AAAA);
+    assert_eq!(question.qclass, RecordClass::IN);
+    assert_eq!(question.to_string(), "example.com.\tIN\tAAAA");
+
+    let question: Question = "example.com. MX".parse().unwrap();
+    assert_eq!(question.qtype, RecordType::MX);
+    assert!("example.com.".parse::<Question>().is_err());
+}
+
+#[test]
+fn message_display() {
+    let name = Name::parse("example.com.", None).unwrap();
+    let mut edns = Edns::new();
+    edns.set_dnssec_ok(true);
+    edns.options.push(EdnsOption {
+        code: EdnsOption::COOKIE,
+        data: vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08],
+    });
+    let message = Message {
+        header: Header {
+            id: 4660,
+            qr: true,
+            rd: true,
+            ra: true,
+            ..Header::new()
+        },
+        questions: vec![Question {
+            qname: name.clone(),
+            qtype: RecordType::A,
+            qclass: RecordClass::IN,
+        }],
+        answers: vec![Record {
+            name,
+            rtype: RecordType::A,
+            rclass: RecordClass::IN,
+            ttl: 300,
+            rdata: RData::A(Ipv4Addr::new(192, 0, 2, 1)),
+        }],
+        authorities: Vec::new(),
+        additionals: vec![edns.to_record()],
+    };
+
+    let expected = "\
+;; ->>HEADER<<- opcode: QUERY, status: NOERROR, id: 4660
+;; flags: qr rd ra; QUERY: 1, ANSWER: 1, AUTHORITY: 0, ADDITIONAL: 1
+
+;; OPT PSEUDOSECTION:
+; EDNS: version: 0, flags: do; udp: 1232
+; COOKIE: 0102030405060708
+
+;; QUESTION SECTION:
+;example.com.\tIN\tA
+
+;; ANSWER SECTION:
+example.com.\t300\tIN\tA\t192.0.2.1
+";
+    assert_eq!(message.to_string(), expected);
+
+    let decoded = Message::from_bytes(&{
+        let mut message = message.clone();
+        message.header.qdcount = 1;
+        message.header.ancount = 1;
+        message.header.arcount = 1;
+        message.to_bytes().unwrap()
+    })
+    .unwrap();
+    assert_eq!(decoded.edns(), Some(edns));
+}
+
+#[test]
+fn message_display_extended_rcode() {
+    let mut edns = Edns::new();
+    edns.udp_payload_size = 4096;
+    let mut message = Message::new();
+    message.additionals.push(edns.to_record());
+    message.set_rcode(Rcode::BadVers);
+
+    let text = message.to_string();
+    assert!(text.starts_with(";; ->>HEADER<<- opcode: QUERY, status: BADVERS, id: 0\n"));
+    assert!(text.contains("; EDNS: version: 0, flags:; udp: 4096\n"));
+    assert!(!text.contains("QUESTION SECTION"));
+}
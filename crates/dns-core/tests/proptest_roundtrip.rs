@@ -0,0 +1,179 @@
+use dns_core::{
+    decompress_name, Flags, Header, Message, MessageRef, Name, Opcode, Question, RData, Rcode,
+    Record, RecordClass, RecordType,
+};
+use proptest::collection::vec;
+use proptest::prelude::*;
+use std::io::Cursor;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+fn name() -> impl Strategy<Value = Name> {
+    vec("[a-zA-Z0-9-]{1,12}", 0..5).prop_map(|labels| Name::from_labels(labels).unwrap())
+}
+
+fn header() -> impl Strategy<Value = Header> {
+    (any::<u16>(), any::<u16>(), 0u8..16, 0u16..16).prop_map(|(id, flags, opcode, rcode)| {
+        let mut header = Header::new();
+        header.id = id;
+        header.set_flags(Flags::from_bits(flags));
+        header.opcode = Opcode::from(opcode);
+        header.rcode = Rcode::from(rcode);
+        header
+    })
+}
+
+fn question() -> impl Strategy<Value = Question> {
+    (name(), any::<u16>(), any::<u16>()).prop_map(|(qname, qtype, qclass)| Question {
+        qname,
+        qtype: RecordType::from(qtype),
+        qclass: RecordClass::from(qclass),
+    })
+}
+
+fn character_string() -> impl Strategy<Value = Vec<u8>> {
+    vec(any::<u8>(), 0..256)
+}
+
+/// RDATA paired with its type. Raw data only uses private-use types, as
+/// known types decode to their typed variants.
+fn rdata() -> impl Strategy<Value = (RecordType, RData)> {
+    prop_oneof![
+        any::<[u8; 4]>().prop_map(|o| (RecordType::A, RData::A(Ipv4Addr::from(o)))),
+        any::<[u8; 16]>().prop_map(|o| (RecordType::AAAA, RData::AAAA(Ipv6Addr::from(o)))),
+        name().prop_map(|n| (RecordType::CNAME, RData::CNAME(n))),
+        name().prop_map(|n| (RecordType::NS, RData::NS(n))),
+        name().prop_map(|n| (RecordType::PTR, RData::PTR(n))),
+        name().prop_map(|n| (RecordType::DNAME, RData::DNAME(n))),
+        (any::<u16>(), name()).prop_map(|(preference, exchange)| (
+            RecordType::MX,
+            RData::MX {
+                preference,
+                exchange
+            }
+        )),
+        (name(), name(), any::<[u32; 5]>()).prop_map(|(mname, rname, t)| (
+            RecordType::SOA,
+            RData::SOA {
+                mname,
+                rname,
+                serial: t[0],
+                refresh: t[1],
+                retry: t[2],
+                expire: t[3],
+                minimum: t[4],
+            }
+        )),
+        vec(character_string(), 1..4).prop_map(|s| (RecordType::TXT, RData::TXT(s))),
+        (any::<[u16; 3]>(), name()).prop_map(|(p, target)| (
+            RecordType::SRV,
+            RData::SRV {
+                priority: p[0],
+                weight: p[1],
+                port: p[2],
+                target,
+            }
+        )),
+        (character_string(), character_string())
+            .prop_map(|(cpu, os)| (RecordType::HINFO, RData::HINFO { cpu, os })),
+        (65280u16..65535, vec(any::<u8>(), 0..64))
+            .prop_map(|(t, data)| (RecordType::from(t), RData::Raw(data))),
+    ]
+}
+
+fn record() -> impl Strategy<Value = Record> {
+    (name(), rdata(), any::<u32>()).prop_map(|(name, (rtype, rdata), ttl)| Record {
+        name,
+        rtype,
+        rclass: RecordClass::IN,
+        ttl,
+        rdata,
+    })
+}
+
+fn message() -> impl Strategy<Value = Message> {
+    (
+        header(),
+        vec(question(), 0..3),
+        vec(record(), 0..4),
+        vec(record(), 0..4),
+        vec(record(), 0..4),
+    )
+        .prop_map(|(header, questions, answers, authorities, additionals)| {
+            let mut message = Message {
+                header,
+                questions,
+                answers,
+                authorities,
+                additionals,
+            };
+            message.update_counts();
+            message
+        })
+}
+
+proptest! {
+    #[test]
+    fn header_roundtrip(header in header()) {
+        let mut buf = Vec::new();
+        header.write(&mut buf).unwrap();
+        prop_assert_eq!(Header::read(&mut Cursor::new(&buf[..])).unwrap(), header);
+    }
+
+    #[test]
+    fn question_roundtrip(question in question()) {
+        let mut buf = Vec::new();
+        question.write(&mut buf).unwrap();
+        let decoded = Question::read(&mut Cursor::new(&buf[..])).unwrap();
+        prop_assert_eq!(decoded, question);
+    }
+
+    #[test]
+    fn record_roundtrip(record in record()) {
+        let mut buf = Vec::new();
+        record.write(&mut buf).unwrap();
+        let mut reader = Cursor::new(&buf[..]);
+        prop_assert_eq!(Record::read(&mut reader).unwrap(), record);
+        prop_assert_eq!(reader.position() as usize, buf.len());
+    }
+
+    #[test]
+    fn message_roundtrip(message in message()) {
+        let bytes = message.to_bytes().unwrap();
+        prop_assert_eq!(Message::from_bytes(&bytes).unwrap(), message);
+    }
+
+    #[test]
+    fn arbitrary_bytes_do_not_panic(bytes in vec(any::<u8>(), 0..512)) {
+        let _ = Message::from_bytes(&bytes);
+        if let Ok(message) = MessageRef::new(&bytes) {
+            for question in message.questions() {
+                let _ = question.map(|q| q.to_question());
+            }
+            for record in message.answers().chain(message.authorities()).chain(message.additionals()) {
+                let _ = record.map(|r| r.to_record());
+            }
+        }
+        for start in 0..bytes.len().min(16) {
+            let mut reader = Cursor::new(&bytes[..]);
+            reader.set_position(start as u64);
+            let _ = decompress_name(&mut reader);
+        }
+    }
+
+    #[test]
+    fn corrupted_messages(
+        message in message(),
+        edits in vec((any::<prop::sample::Index>(), any::<u8>()), 1..8),
+    ) {
+        let mut bytes = message.to_bytes().unwrap();
+        for (index, value) in edits {
+            let i = index.index(bytes.len());
+            bytes[i] = value;
+        }
+        // Whatever still decodes must survive re-encoding unchanged.
+        if let Ok(decoded) = Message::from_bytes(&bytes) {
+            let bytes = decoded.to_bytes().unwrap();
+            prop_assert_eq!(Message::from_bytes(&bytes).unwrap(), decoded);
+        }
+    }
+}
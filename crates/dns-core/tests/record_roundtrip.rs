@@ -0,0 +1,252 @@
+use dns_core::{
+    DecodeError, Ds, Message, Name, Nsec, Nsec3, RData, Record, RecordClass, RecordType, Rrsig,
+    SvcParam, SvcParamKey, Svcb, SvcbError, TypeBitmap,
+};
+
+fn name(s: &str) -> Name {
+    s.parse().unwrap()
+}
+
+fn roundtrip(rtype: RecordType, rdata: RData) {
+    let record = Record {
+        name: name("example.com."),
+        rtype,
+        rclass: RecordClass::IN,
+        ttl: 3600,
+        rdata,
+    };
+    let mut message = Message::new();
+    message.header.ancount = 1;
+    message.answers.push(record);
+
+    let bytes = message.to_bytes().unwrap();
+    assert_eq!(Message::from_bytes(&bytes).unwrap(), message);
+
+    let mut uncompressed = Vec::new();
+    message.answers[0].write(&mut uncompressed).unwrap();
+    let mut reader = std::io::Cursor::new(&uncompressed[..]);
+    assert_eq!(Record::read(&mut reader).unwrap(), message.answers[0]);
+}
+
+#[test]
+fn ptr_roundtrip() {
+    roundtrip(RecordType::PTR, RData::PTR(name("host.example.com.")));
+}
+
+#[test]
+fn srv_roundtrip() {
+    roundtrip(
+        RecordType::SRV,
+        RData::SRV {
+            priority: 10,
+            weight: 60,
+            port: 5060,
+            target: name("sip.example.com."),
+        },
+    );
+}
+
+#[test]
+fn naptr_roundtrip() {
+    roundtrip(
+        RecordType::NAPTR,
+        RData::NAPTR {
+            order: 100,
+            preference: 10,
+            flags: b"u".to_vec(),
+            services: b"E2U+sip".to_vec(),
+            regexp: b"!^.*$!sip:info@example.com!".to_vec(),
+            replacement: Name::root(),
+        },
+    );
+}
+
+#[test]
+fn uri_roundtrip() {
+    roundtrip(
+        RecordType::URI,
+        RData::URI {
+            priority: 10,
+            weight: 1,
+            target: "ftp://ftp1.example.com/public".to_string(),
+        },
+    );
+}
+
+#[test]
+fn srv_target_is_not_compressed() {
+    let mut message = Message::new();
+    message.header.ancount = 1;
+    message.answers.push(Record {
+        name: name("_sip._udp.example.com."),
+        rtype: RecordType::SRV,
+        rclass: RecordClass::IN,
+        ttl: 3600,
+        rdata: RData::SRV {
+            priority: 0,
+            weight: 0,
+            port: 5060,
+            target: name("example.com."),
+        },
+    });
+    let bytes = message.to_bytes().unwrap();
+    assert!(bytes.ends_with(&name("example.com.").to_wire()));
+}
+
+#[test]
+fn https_roundtrip() {
+    roundtrip(
+        RecordType::HTTPS,
+        RData::HTTPS(Svcb {
+            priority: 1,
+            target: name("svc.example.net."),
+            params: vec![
+                SvcParam::Mandatory(vec![SvcParamKey::Alpn]),
+                SvcParam::Alpn(vec![b"h2".to_vec(), b"h3".to_vec()]),
+                SvcParam::NoDefaultAlpn,
+                SvcParam::Ipv6Hint(vec!["2001:db8::1".parse().unwrap()]),
+                SvcParam::DohPath("/dns-query{?dns}".to_string()),
+                SvcParam::Unknown(667, b"hello".to_vec()),
+            ],
+        }),
+    );
+}
+
+#[test]
+fn svcb_rejects_unordered_keys() {
+    // SVCB 1 . port=53 alpn=h2, with the keys in the wrong order.
+    let rdata = [0, 1, 0, 0, 3, 0, 2, 0, 53, 0, 1, 0, 3, 2, b'h', b'2'];
+    let mut reader = std::io::Cursor::new(&rdata[..]);
+    assert!(matches!(
+        RData::read(&mut reader, RecordType::SVCB, rdata.len() as u16),
+        Err(DecodeError::InvalidRData { offset: 9, .. })
+    ));
+
+    let unordered = Svcb {
+        priority: 1,
+        target: Name::root(),
+        params: vec![SvcParam::Port(53), SvcParam::Alpn(vec![b"h2".to_vec()])],
+    };
+    assert_eq!(unordered.validate(), Err(SvcbError::KeyOrder));
+}
+
+#[test]
+fn type_bitmap_wire_format() {
+    // RFC 4034 section 4.3: A MX RRSIG NSEC TYPE1234.
+    let types: TypeBitmap = [
+        RecordType::A,
+        RecordType::MX,
+        RecordType::RRSIG,
+        RecordType::NSEC,
+        RecordType::Unknown(1234),
+    ]
+    .into_iter()
+    .collect();
+    let rdata = RData::NSEC(Nsec {
+        next_domain: name("host.example.com."),
+        types,
+    });
+    let bytes = rdata.to_bytes().unwrap();
+    let bitmap = &bytes[name("host.example.com.").wire_len()..];
+    let mut expected = vec![0x00, 0x06, 0x40, 0x01, 0x00, 0x00, 0x00, 0x03, 0x04, 0x1b];
+    expected.extend_from_slice(&[0; 26]);
+    expected.push(0x20);
+    assert_eq!(bitmap, expected);
+    roundtrip(RecordType::NSEC, rdata);
+
+    // A block with a trailing zero octet is malformed.
+    let malformed = [0, 0, 2, 0x40, 0x00];
+    let mut reader = std::io::Cursor::new(&malformed[..]);
+    assert!(RData::read(&mut reader, RecordType::NSEC, malformed.len() as u16).is_err());
+}
+
+#[test]
+fn dnssec_roundtrip() {
+    roundtrip(
+        RecordType::RRSIG,
+        RData::RRSIG(Rrsig {
+            type_covered: RecordType::A,
+            algorithm: 13,
+            labels: 2,
+            original_ttl: 3600,
+            expiration: 1_700_000_000,
+            inception: 1_690_000_000,
+            key_tag: 12345,
+            signer_name: name("example.com."),
+            signature: vec![1, 2, 3, 4],
+        }),
+    );
+    roundtrip(
+        RecordType::NSEC3,
+        RData::NSEC3(Nsec3 {
+            hash_algorithm: 1,
+            flags: 0,
+            iterations: 0,
+            salt: Vec::new(),
+            next_hashed_owner: vec![0x5A; 20],
+            types: TypeBitmap::new(),
+        }),
+    );
+    roundtrip(
+        RecordType::CDS,
+        RData::CDS(Ds {
+            key_tag: 0,
+            algorithm: 0,
+            digest_type: 0,
+            digest: vec![0],
+        }),
+    );
+}
+
+#[test]
+fn txt_roundtrip() {
+    let rdata = RData::txt(vec![0xC3; 300]);
+    assert_eq!(rdata, RData::TXT(vec![vec![0xC3; 255], vec![0xC3; 45]]));
+    assert_eq!(rdata.to_bytes().unwrap().len(), 302);
+    roundtrip(RecordType::TXT, rdata);
+
+    let too_long = RData::TXT(vec![vec![b'x'; 256]]);
+    assert!(too_long.to_bytes().is_err());
+
+    // A length prefix running past the RDATA is rejected.
+    let rdata = [3, b'a', b'b'];
+    let mut reader = std::io::Cursor::new(&rdata[..]);
+    assert!(RData::read(&mut reader, RecordType::TXT, 3).is_err());
+}
+
+#[test]
+fn unknown_type_message_roundtrip() {
+    let record = Record {
+        name: name("a.example."),
+        rtype: RecordType::Unknown(65280),
+        rclass: RecordClass::Unknown(32),
+        ttl: 60,
+        rdata: RData::Raw(vec![0xC0, 0x0C, 0xFF]),
+    };
+    let mut message = Message::new();
+    message.header.ancount = 1;
+    message.answers.push(record);
+
+    let bytes = message.to_bytes().unwrap();
+    let decoded = Message::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded, message);
+    assert_eq!(decoded.answers[0].rtype.to_string(), "TYPE65280");
+    assert_eq!(decoded.answers[0].rclass.to_string(), "CLASS32");
+}
+
+#[test]
+fn oversized_rdata_is_rejected() {
+    let record = Record {
+        name: name("a.example."),
+        rtype: RecordType::TXT,
+        rclass: RecordClass::IN,
+        ttl: 60,
+        rdata: RData::TXT(vec![vec![b'x'; 255]; 300]),
+    };
+    let err = record.write(&mut Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    let mut message = Message::new();
+    message.answers.push(record);
+    assert!(message.to_bytes().is_err());
+}
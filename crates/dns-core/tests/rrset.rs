@@ -0,0 +1,71 @@
+use dns_core::{RData, RRset, Record};
+
+fn record(line: &str) -> Record {
+    line.parse().unwrap()
+}
+
+#[test]
+fn group_records() {
+    let records = vec![
+        record("example.com. 300 IN A 192.0.2.2"),
+        record("example.com. 300 IN MX 10 mail.example.com."),
+        record("EXAMPLE.com. 60 IN A 192.0.2.1"),
+        record("example.com. 300 IN A 192.0.2.2"),
+        record("example.com. 300 CH A 192.0.2.3"),
+    ];
+    let rrsets = RRset::from_records(records);
+    assert_eq!(rrsets.len(), 3);
+    assert_eq!(rrsets[0].len(), 2);
+    assert_eq!(rrsets[0].ttl, 60);
+    assert_eq!(rrsets[1].len(), 1);
+
+    let records = rrsets[0].clone().into_records();
+    assert!(records.iter().all(|r| r.ttl == 60));
+    assert_eq!(records, rrsets[0].records().collect::<Vec<_>>());
+    assert!(!rrsets[1].clone().insert(records[0].clone()));
+}
+
+#[test]
+fn canonical_order() {
+    let mut rrset = RRset::from_records(vec![
+        record("example.com. 300 IN MX 20 b.example.com."),
+        record("example.com. 300 IN MX 10 Z.example.com."),
+        record("example.com. 300 IN MX 10 a.example.com."),
+    ])
+    .remove(0);
+    rrset.rdata.push(RData::MX {
+        preference: 10,
+        exchange: "z.EXAMPLE.com.".parse().unwrap(),
+    });
+    rrset.sort_canonical().unwrap();
+    let exchanges: Vec<String> = rrset
+        .rdata
+        .iter()
+        .map(|rdata| match rdata {
+            RData::MX { exchange, .. } => exchange.to_string(),
+            _ => unreachable!(),
+        })
+        .collect();
+    assert_eq!(
+        exchanges,
+        ["a.example.com.", "Z.example.com.", "b.example.com."]
+    );
+}
+
+#[test]
+fn canonical_wire_form() {
+    let rrset = RRset::from_records(vec![
+        record("Host.Example. 300 IN CNAME Target.Example."),
+        record("Host.Example. 300 IN CNAME target.example."),
+    ])
+    .remove(0);
+    assert_eq!(rrset.len(), 1);
+    let mut expected = b"\x04host\x07example\x00".to_vec();
+    expected.extend_from_slice(&[0, 5, 0, 1, 0, 0, 1, 44, 0, 16]);
+    expected.extend_from_slice(b"\x06target\x07example\x00");
+    assert_eq!(rrset.to_canonical_wire().unwrap(), expected);
+
+    // NSEC next names keep their case (RFC 6840 section 5.1).
+    let nsec = record("a.example. 300 IN NSEC B.example. A");
+    assert_eq!(nsec.rdata.to_canonical_bytes().unwrap()[1], b'B');
+}
@@ -0,0 +1,93 @@
+use dns_core::{Edns, Message, Name, RData, Record, RecordClass, RecordType};
+use std::net::Ipv4Addr;
+
+fn records(name: &str, rtype: RecordType, count: u8) -> Vec<Record> {
+    let name = Name::parse(name, None).unwrap();
+    (0..count)
+        .map(|i| Record {
+            name: name.clone(),
+            rtype,
+            rclass: RecordClass::IN,
+            ttl: 300,
+            rdata: match rtype {
+                RecordType::A => RData::A(Ipv4Addr::new(192, 0, 2, i)),
+                _ => RData::txt(vec![b'x'; 200]),
+            },
+        })
+        .collect()
+}
+
+fn response(edns: Option<Edns>) -> Message {
+    let query = Message::query(Name::parse("example.com.", None).unwrap(), RecordType::A).build();
+    let mut builder = Message::response_to(&query)
+        .answers(records("example.com.", RecordType::A, 4))
+        .authorities(records("example.com.", RecordType::TXT, 2))
+        .additionals(records("a.example.com.", RecordType::TXT, 1))
+        .additionals(records("b.example.com.", RecordType::TXT, 1));
+    if let Some(edns) = edns {
+        builder = builder.edns(edns);
+    }
+    builder.build()
+}
+
+#[test]
+fn message_that_fits_is_unchanged() {
+    let message = response(None);
+    assert_eq!(
+        message.to_wire_limited(65535).unwrap(),
+        message.to_bytes().unwrap()
+    );
+}
+
+#[test]
+fn drops_additional_then_authority() {
+    let message = response(Some(Edns::new()));
+    let full = message.to_bytes().unwrap().len();
+
+    let limited = Message::from_bytes(&message.to_wire_limited(full - 1).unwrap()).unwrap();
+    assert!(!limited.header.tc);
+    assert_eq!(limited.authorities.len(), 2);
+    // The b.example.com. RRset goes first, and the OPT record stays.
+    assert_eq!(limited.additionals.len(), 2);
+    assert_eq!(limited.additionals[0].name.to_string(), "a.example.com.");
+    assert!(limited.edns().is_some());
+
+    let limited = Message::from_bytes(&message.to_wire_limited(512).unwrap()).unwrap();
+    assert!(!limited.header.tc);
+    assert_eq!(limited.answers.len(), 4);
+    assert!(limited.authorities.is_empty());
+    assert_eq!(limited.additionals.len(), 1);
+    assert_eq!(limited.additionals[0].rtype, RecordType::OPT);
+}
+
+#[test]
+fn sets_tc_when_answers_do_not_fit() {
+    let message = response(None);
+    let bytes = message.to_wire_limited(60).unwrap();
+    assert!(bytes.len() <= 60);
+    let limited = Message::from_bytes(&bytes).unwrap();
+    assert!(limited.header.tc);
+    assert!(limited.answers.is_empty());
+    assert_eq!(limited.questions, message.questions);
+
+    assert!(message.to_wire_limited(12).is_err());
+}
+
+#[test]
+fn udp_payload_size() {
+    let name = Name::parse("example.com.", None).unwrap();
+    let query = Message::query(name.clone(), RecordType::A).build();
+    assert_eq!(query.udp_payload_size(), 512);
+
+    let mut edns = Edns::new();
+    edns.udp_payload_size = 4096;
+    let query = Message::query(name.clone(), RecordType::A)
+        .edns(edns)
+        .build();
+    assert_eq!(query.udp_payload_size(), 4096);
+
+    let mut edns = Edns::new();
+    edns.udp_payload_size = 100;
+    let query = Message::query(name, RecordType::A).edns(edns).build();
+    assert_eq!(query.udp_payload_size(), 512);
+}
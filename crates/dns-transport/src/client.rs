@@ -0,0 +1,253 @@
+use dns_core::builder::random_id;
+use dns_core::error::DecodeError;
+use dns_core::message::Message;
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream, UdpSocket};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("IO Error: {0}")]
+    Io(#[from] io::Error),
+    #[error("No response from {0}")]
+    Timeout(SocketAddr),
+    #[error("Invalid response: {0}")]
+    Decode(#[from] DecodeError),
+    #[error("Response does not match the query")]
+    Mismatch,
+}
+
+/// The largest message a UDP datagram or TCP frame can carry.
+const MAX_MESSAGE_SIZE: usize = u16::MAX as usize;
+
+/// Sends queries to a single server, over UDP first and over TCP when the
+/// answer is truncated.
+#[derive(Debug, Clone)]
+pub struct Client {
+    server: SocketAddr,
+    timeout: Duration,
+    retries: u32,
+    tcp_fallback: bool,
+}
+
+impl Client {
+    /// A client waiting two seconds for the first UDP attempt and retrying
+    /// twice.
+    pub fn new(server: SocketAddr) -> Self {
+        Client {
+            server,
+            timeout: Duration::from_secs(2),
+            retries: 2,
+            tcp_fallback: true,
+        }
+    }
+
+    pub fn server(&self) -> SocketAddr {
+        self.server
+    }
+
+    /// Time allowed for the first UDP attempt and for each TCP exchange.
+    /// Every UDP retry waits twice as long as the attempt before it.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Whether truncated UDP responses are retried over TCP.
+    pub fn tcp_fallback(mut self, tcp_fallback: bool) -> Self {
+        self.tcp_fallback = tcp_fallback;
+        self
+    }
+
+    /// Sends `query` over UDP, re-sending it over TCP if the response has
+    /// the TC bit set. Each attempt uses a new random ID, which the response
+    /// carries.
+    pub fn query(&self, query: &Message) -> Result<Message, ClientError> {
+        let response = self.query_udp(query)?;
+        if response.header.tc && self.tcp_fallback {
+            return self.query_tcp(query);
+        }
+        Ok(response)
+    }
+
+    /// Sends `query` over UDP from a new socket on a random port for every
+    /// attempt. Datagrams from other addresses, or with the wrong ID or
+    /// question, are ignored as possible spoofing attempts.
+    pub fn query_udp(&self, query: &Message) -> Result<Message, ClientError> {
+        let mut timeout = self.timeout;
+        let mut buf = vec![0u8; MAX_MESSAGE_SIZE];
+        for _ in 0..=self.retries {
+            let (query, bytes) = prepare(query)?;
+            let socket = UdpSocket::bind(unspecified(self.server))?;
+            socket.connect(self.server)?;
+            socket.send(&bytes)?;
+
+            let deadline = Instant::now() + timeout;
+            loop {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    break;
+                }
+                socket.set_read_timeout(Some(remaining))?;
+                match socket.recv_from(&mut buf) {
+                    Ok((len, src)) => {
+                        if let Some(response) = validate(&query, self.server, src, &buf[..len]) {
+                            return Ok(response);
+                        }
+                    }
+                    Err(e) if is_timeout(&e) => break,
+                    Err(e) => return Err(e.into()),
+                }
+            }
+            timeout = timeout.saturating_mul(2);
+        }
+        Err(ClientError::Timeout(self.server))
+    }
+
+    /// Sends `query` over a new TCP connection.
+    pub fn query_tcp(&self, query: &Message) -> Result<Message, ClientError> {
+        let (query, bytes) = prepare(query)?;
+        let mut stream = TcpStream::connect_timeout(&self.server, self.timeout)
+            .map_err(|e| self.timeout_error(e))?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+        stream
+            .write_all(&frame(&bytes)?)
+            .map_err(|e| self.timeout_error(e))?;
+
+        let mut len = [0u8; 2];
+        stream
+            .read_exact(&mut len)
+            .map_err(|e| self.timeout_error(e))?;
+        let mut buf = vec![0u8; u16::from_be_bytes(len) as usize];
+        stream
+            .read_exact(&mut buf)
+            .map_err(|e| self.timeout_error(e))?;
+        check_tcp_response(&query, &buf)
+    }
+
+    fn timeout_error(&self, e: io::Error) -> ClientError {
+        if is_timeout(&e) {
+            ClientError::Timeout(self.server)
+        } else {
+            e.into()
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Client {
+    /// The async counterpart of [`Client::query`].
+    pub async fn query_async(&self, query: &Message) -> Result<Message, ClientError> {
+        let response = self.query_udp_async(query).await?;
+        if response.header.tc && self.tcp_fallback {
+            return self.query_tcp_async(query).await;
+        }
+        Ok(response)
+    }
+
+    /// The async counterpart of [`Client::query_udp`].
+    pub async fn query_udp_async(&self, query: &Message) -> Result<Message, ClientError> {
+        let mut timeout = self.timeout;
+        let mut buf = vec![0u8; MAX_MESSAGE_SIZE];
+        for _ in 0..=self.retries {
+            let (query, bytes) = prepare(query)?;
+            let socket = tokio::net::UdpSocket::bind(unspecified(self.server)).await?;
+            socket.connect(self.server).await?;
+            socket.send(&bytes).await?;
+
+            let deadline = tokio::time::Instant::now() + timeout;
+            while let Ok(received) =
+                tokio::time::timeout_at(deadline, socket.recv_from(&mut buf)).await
+            {
+                let (len, src) = received?;
+                if let Some(response) = validate(&query, self.server, src, &buf[..len]) {
+                    return Ok(response);
+                }
+            }
+            timeout = timeout.saturating_mul(2);
+        }
+        Err(ClientError::Timeout(self.server))
+    }
+
+    /// The async counterpart of [`Client::query_tcp`].
+    pub async fn query_tcp_async(&self, query: &Message) -> Result<Message, ClientError> {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let (query, bytes) = prepare(query)?;
+        let exchange = async {
+            let mut stream = tokio::net::TcpStream::connect(self.server).await?;
+            stream.write_all(&frame(&bytes)?).await?;
+            let len = stream.read_u16().await?;
+            let mut buf = vec![0u8; len as usize];
+            stream.read_exact(&mut buf).await?;
+            Ok::<_, io::Error>(buf)
+        };
+        let buf = tokio::time::timeout(self.timeout, exchange)
+            .await
+            .map_err(|_| ClientError::Timeout(self.server))??;
+        check_tcp_response(&query, &buf)
+    }
+}
+
+/// A copy of `query` with a fresh random ID, and its wire form.
+fn prepare(query: &Message) -> io::Result<(Message, Vec<u8>)> {
+    let mut query = query.clone();
+    query.header.id = random_id();
+    let bytes = query.to_bytes()?;
+    Ok((query, bytes))
+}
+
+fn frame(bytes: &[u8]) -> io::Result<Vec<u8>> {
+    let len = u16::try_from(bytes.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Query too long for TCP"))?;
+    let mut frame = Vec::with_capacity(2 + bytes.len());
+    frame.extend_from_slice(&len.to_be_bytes());
+    frame.extend_from_slice(bytes);
+    Ok(frame)
+}
+
+/// The response in a datagram, if it comes from the server and answers
+/// `query`.
+fn validate(query: &Message, server: SocketAddr, src: SocketAddr, bytes: &[u8]) -> Option<Message> {
+    if src != server {
+        return None;
+    }
+    let response = Message::from_bytes(bytes).ok()?;
+    answers(query, &response).then_some(response)
+}
+
+fn check_tcp_response(query: &Message, bytes: &[u8]) -> Result<Message, ClientError> {
+    let response = Message::from_bytes(bytes)?;
+    if !answers(query, &response) {
+        return Err(ClientError::Mismatch);
+    }
+    Ok(response)
+}
+
+fn answers(query: &Message, response: &Message) -> bool {
+    response.header.qr
+        && response.header.id == query.header.id
+        && response.questions == query.questions
+}
+
+fn unspecified(server: SocketAddr) -> SocketAddr {
+    let ip = match server {
+        SocketAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        SocketAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+    };
+    SocketAddr::new(ip, 0)
+}
+
+fn is_timeout(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    )
+}
@@ -0,0 +1,100 @@
+use dns_core::message::Message;
+use std::fmt;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::time::Instant;
+use tokio::sync::mpsc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Protocol {
+    Udp,
+    Tcp,
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Protocol::Udp => "UDP",
+            Protocol::Tcp => "TCP",
+        })
+    }
+}
+
+/// A query together with where and how it arrived.
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub message: Message,
+    pub src: SocketAddr,
+    pub protocol: Protocol,
+    pub received_at: Instant,
+    /// Length of the query on the wire, excluding any TCP length prefix.
+    pub raw_len: usize,
+}
+
+impl Request {
+    /// The largest response the client accepts: its EDNS payload size over
+    /// UDP, and the 65535 bytes a TCP frame can carry otherwise.
+    pub fn max_response_size(&self) -> usize {
+        match self.protocol {
+            Protocol::Udp => self.message.udp_payload_size(),
+            Protocol::Tcp => u16::MAX as usize,
+        }
+    }
+}
+
+/// What to send back for a request.
+#[derive(Debug)]
+pub enum Response {
+    /// Send nothing, e.g. to silently drop a query.
+    None,
+    Message(Message),
+    /// Messages sent in order as they arrive, as for a zone transfer. Over
+    /// UDP only the first one is sent.
+    Stream(mpsc::Receiver<Message>),
+}
+
+impl Response {
+    /// A stream response fed through the returned sender. The stream ends
+    /// once every sender is dropped.
+    pub fn channel(buffer: usize) -> (mpsc::Sender<Message>, Response) {
+        let (sender, receiver) = mpsc::channel(buffer);
+        (sender, Response::Stream(receiver))
+    }
+
+    pub fn from_messages(messages: Vec<Message>) -> Response {
+        let (sender, response) = Response::channel(messages.len().max(1));
+        for message in messages {
+            // The channel has room for every message.
+            let _ = sender.try_send(message);
+        }
+        response
+    }
+}
+
+impl From<Message> for Response {
+    fn from(message: Message) -> Self {
+        Response::Message(message)
+    }
+}
+
+impl From<Option<Message>> for Response {
+    fn from(message: Option<Message>) -> Self {
+        message.map_or(Response::None, Response::Message)
+    }
+}
+
+/// Answers requests for a server. Implemented for closures taking a
+/// [`Request`] and returning a future of the [`Response`].
+pub trait RequestHandler: Send + Sync + 'static {
+    fn handle(&self, request: Request) -> impl Future<Output = Response> + Send;
+}
+
+impl<F, Fut> RequestHandler for F
+where
+    F: Fn(Request) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Response> + Send,
+{
+    fn handle(&self, request: Request) -> impl Future<Output = Response> + Send {
+        self(request)
+    }
+}
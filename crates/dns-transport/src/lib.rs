@@ -1,2 +1,15 @@
+pub mod client;
+#[cfg(feature = "tokio")]
+pub mod handler;
+#[cfg(feature = "tokio")]
+pub mod server;
+#[cfg(feature = "tokio")]
 pub mod tcp_server;
+#[cfg(feature = "tokio")]
 pub mod udp_server;
+
+pub use client::{Client, ClientError};
+#[cfg(feature = "tokio")]
+pub use handler::{Protocol, Request, RequestHandler, Response};
+#[cfg(feature = "tokio")]
+pub use server::{Server, ServerHandle};
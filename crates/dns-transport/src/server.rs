@@ -0,0 +1,530 @@
+use crate::handler::{Protocol, Request, RequestHandler, Response};
+use dns_core::message::Message;
+use dns_core::types::{Opcode, Rcode};
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs, UdpSocket};
+use tokio::sync::{oneshot, watch};
+use tokio::task::JoinSet;
+
+/// Controls a running server. Dropping the handle leaves the server
+/// running.
+#[derive(Debug)]
+pub struct ServerHandle {
+    local_addrs: Vec<SocketAddr>,
+    shutdown: watch::Sender<Option<Instant>>,
+    done: oneshot::Receiver<io::Result<()>>,
+}
+
+impl ServerHandle {
+    /// The address of the first socket the server listens on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addrs[0]
+    }
+
+    pub fn local_addrs(&self) -> &[SocketAddr] {
+        &self.local_addrs
+    }
+
+    /// Stops accepting queries and closes idle TCP connections. Queries
+    /// already being handled may finish within `grace`, after which they
+    /// are abandoned.
+    pub fn shutdown(&self, grace: Duration) {
+        let deadline = Instant::now() + grace;
+        self.shutdown.send_if_modified(|current| match current {
+            Some(current) if *current <= deadline => false,
+            _ => {
+                *current = Some(deadline);
+                true
+            }
+        });
+    }
+
+    /// Waits until the server has stopped, returning the error of the
+    /// socket that failed, if any.
+    pub async fn stopped(self) -> io::Result<()> {
+        self.done
+            .await
+            .unwrap_or_else(|_| Err(stopped_without_result()))
+    }
+
+    /// Blocks until the server has stopped, for handles returned by
+    /// `start_udp_server` and `start_tcp_server`. Use [`ServerHandle::stopped`]
+    /// inside a runtime instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from within an async runtime.
+    pub fn join(self) -> io::Result<()> {
+        self.done
+            .blocking_recv()
+            .unwrap_or_else(|_| Err(stopped_without_result()))
+    }
+}
+
+fn stopped_without_result() -> io::Error {
+    io::Error::other("server stopped without a result")
+}
+
+/// Resolves to the shutdown deadline once one is set. Never resolves if
+/// the handle was dropped without shutting down.
+async fn shutdown_requested(shutdown: &mut watch::Receiver<Option<Instant>>) -> Instant {
+    loop {
+        if let Some(deadline) = *shutdown.borrow_and_update() {
+            return deadline;
+        }
+        if shutdown.changed().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+/// Waits for the remaining tasks until `deadline`, aborting any left.
+async fn drain(mut tasks: JoinSet<()>, deadline: Instant) {
+    let _ = tokio::time::timeout_at(deadline.into(), async {
+        while tasks.join_next().await.is_some() {}
+    })
+    .await;
+}
+
+/// A DNS server answering over any number of UDP sockets and TCP listeners
+/// on a tokio runtime, with every query handled in its own task.
+pub struct Server<H> {
+    handler: Arc<H>,
+    udp: Vec<UdpSocket>,
+    tcp: Vec<TcpListener>,
+    udp_max_in_flight: usize,
+    tcp_config: TcpConfig,
+}
+
+impl<H: RequestHandler> Server<H> {
+    pub fn new(handler: H) -> Self {
+        Server {
+            handler: Arc::new(handler),
+            udp: Vec::new(),
+            tcp: Vec::new(),
+            udp_max_in_flight: 256,
+            tcp_config: TcpConfig::default(),
+        }
+    }
+
+    /// How many queries of one UDP socket are handled at once; further
+    /// datagrams are not read until one completes. Defaults to 256.
+    pub fn udp_max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.udp_max_in_flight = max_in_flight.max(1);
+        self
+    }
+
+    /// How long a TCP connection may stay open without queries in flight
+    /// before the server closes it. Defaults to ten seconds.
+    pub fn tcp_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.tcp_config.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// How many queries of one TCP connection are handled at once; further
+    /// queries are not read until one completes. Defaults to 32.
+    pub fn tcp_max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.tcp_config.max_in_flight = max_in_flight.max(1);
+        self
+    }
+
+    /// Binds a UDP socket, returning its local address.
+    pub async fn bind_udp<A: ToSocketAddrs>(&mut self, addr: A) -> io::Result<SocketAddr> {
+        let socket = UdpSocket::bind(addr).await?;
+        let local_addr = socket.local_addr()?;
+        self.udp.push(socket);
+        Ok(local_addr)
+    }
+
+    /// Binds a TCP listener, returning its local address.
+    pub async fn bind_tcp<A: ToSocketAddrs>(&mut self, addr: A) -> io::Result<SocketAddr> {
+        let listener = TcpListener::bind(addr).await?;
+        let local_addr = listener.local_addr()?;
+        self.tcp.push(listener);
+        Ok(local_addr)
+    }
+
+    pub fn add_udp_socket(&mut self, socket: UdpSocket) {
+        self.udp.push(socket);
+    }
+
+    pub fn add_tcp_listener(&mut self, listener: TcpListener) {
+        self.tcp.push(listener);
+    }
+
+    pub fn local_addrs(&self) -> io::Result<Vec<SocketAddr>> {
+        let udp = self.udp.iter().map(UdpSocket::local_addr);
+        let tcp = self.tcp.iter().map(TcpListener::local_addr);
+        udp.chain(tcp).collect()
+    }
+
+    /// Serves on every bound socket. Returns once a socket fails, with its
+    /// error, or immediately if nothing is bound.
+    pub async fn run(self) -> io::Result<()> {
+        let (_, shutdown) = watch::channel(None);
+        self.run_until_shutdown(shutdown).await
+    }
+
+    /// Spawns the server onto the current runtime, returning a handle to
+    /// stop it. Fails if nothing is bound.
+    pub fn start(self) -> io::Result<ServerHandle> {
+        let local_addrs = self.local_addrs()?;
+        if local_addrs.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "no sockets bound",
+            ));
+        }
+        let (shutdown, shutdown_rx) = watch::channel(None);
+        let (done_tx, done) = oneshot::channel();
+        tokio::spawn(async move {
+            report(done_tx, self.run_until_shutdown(shutdown_rx).await);
+        });
+        Ok(ServerHandle {
+            local_addrs,
+            shutdown,
+            done,
+        })
+    }
+
+    async fn run_until_shutdown(
+        self,
+        shutdown: watch::Receiver<Option<Instant>>,
+    ) -> io::Result<()> {
+        let mut tasks = JoinSet::new();
+        for socket in self.udp {
+            tasks.spawn(serve_udp(
+                Arc::new(socket),
+                Arc::clone(&self.handler),
+                self.udp_max_in_flight,
+                shutdown.clone(),
+            ));
+        }
+        for listener in self.tcp {
+            tasks.spawn(serve_tcp(
+                listener,
+                Arc::clone(&self.handler),
+                self.tcp_config,
+                shutdown.clone(),
+            ));
+        }
+        while let Some(result) = tasks.join_next().await {
+            result.map_err(io::Error::other)??;
+        }
+        Ok(())
+    }
+}
+
+/// Hands the result of a server to its handle, or prints the error if the
+/// handle is gone.
+fn report(done: oneshot::Sender<io::Result<()>>, result: io::Result<()>) {
+    if let Err(Err(e)) = done.send(result) {
+        eprintln!("DNS server error: {}", e);
+    }
+}
+
+/// Answers the queries arriving on `socket`, up to `max_in_flight` at once.
+/// Receive errors, such as those caused by ICMP messages about earlier
+/// responses, are reported and skipped. Datagrams that are not queries are
+/// ignored.
+async fn serve_udp<H: RequestHandler>(
+    socket: Arc<UdpSocket>,
+    handler: Arc<H>,
+    max_in_flight: usize,
+    mut shutdown: watch::Receiver<Option<Instant>>,
+) -> io::Result<()> {
+    let mut queries = JoinSet::new();
+    // Queries carrying EDNS options may exceed the classic 512 bytes.
+    let mut buf = [0u8; 4096];
+    let deadline = loop {
+        let (size, src) = tokio::select! {
+            received = socket.recv_from(&mut buf), if queries.len() < max_in_flight => {
+                match received {
+                    Ok(received) => received,
+                    Err(e) => {
+                        eprintln!("UDP server receive error: {}", e);
+                        continue;
+                    }
+                }
+            }
+            deadline = shutdown_requested(&mut shutdown) => break deadline,
+            Some(_) = queries.join_next(), if !queries.is_empty() => continue,
+        };
+        let received_at = Instant::now();
+        let Ok(message) = Message::from_bytes(&buf[..size]) else {
+            continue;
+        };
+        if message.header.qr {
+            continue;
+        }
+        let request = Request {
+            message,
+            src,
+            protocol: Protocol::Udp,
+            received_at,
+            raw_len: size,
+        };
+        let socket = Arc::clone(&socket);
+        let handler = Arc::clone(&handler);
+        queries.spawn(async move {
+            let max_size = request.max_response_size();
+            let response = match handler.handle(request).await {
+                Response::None => return,
+                Response::Message(message) => message,
+                Response::Stream(mut messages) => match messages.recv().await {
+                    Some(message) => message,
+                    None => return,
+                },
+            };
+            if let Ok(response_buf) = response.to_wire_limited(max_size) {
+                let _ = socket.send_to(&response_buf, src).await;
+            }
+        });
+    };
+    drain(queries, deadline).await;
+    Ok(())
+}
+
+/// Limits applied to every TCP connection.
+#[derive(Debug, Clone, Copy)]
+struct TcpConfig {
+    idle_timeout: Duration,
+    max_in_flight: usize,
+}
+
+impl Default for TcpConfig {
+    fn default() -> Self {
+        TcpConfig {
+            idle_timeout: Duration::from_secs(10),
+            max_in_flight: 32,
+        }
+    }
+}
+
+async fn serve_tcp<H: RequestHandler>(
+    listener: TcpListener,
+    handler: Arc<H>,
+    config: TcpConfig,
+    mut shutdown: watch::Receiver<Option<Instant>>,
+) -> io::Result<()> {
+    let mut connections = JoinSet::new();
+    let deadline = loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, src)) => {
+                    connections.spawn(handle_tcp_client(
+                        stream,
+                        src,
+                        Arc::clone(&handler),
+                        config,
+                        shutdown.clone(),
+                    ));
+                }
+                Err(e) => {
+                    eprintln!("TCP server connection error: {}", e);
+                }
+            },
+            deadline = shutdown_requested(&mut shutdown) => break deadline,
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
+        }
+    };
+    drop(listener);
+    drain(connections, deadline).await;
+    Ok(())
+}
+
+/// Serves the queries pipelined on one connection (RFC 7766 section 6.2.1).
+/// Up to `max_in_flight` queries are handled concurrently and their
+/// responses written as they complete, in any order. The connection is
+/// closed once it has been idle for `idle_timeout`, after the client closes
+/// it, or on shutdown, in each case after answering the queries received.
+async fn handle_tcp_client<H: RequestHandler>(
+    stream: TcpStream,
+    src: SocketAddr,
+    handler: Arc<H>,
+    config: TcpConfig,
+    mut shutdown: watch::Receiver<Option<Instant>>,
+) {
+    let (reader, writer) = stream.into_split();
+    // Reading happens in a task of its own so that a frame is never lost
+    // half-read when another event wakes the loop below.
+    let (frame_tx, mut frames) = tokio::sync::mpsc::channel(1);
+    let mut io_tasks = JoinSet::new();
+    io_tasks.spawn(read_frames(reader, frame_tx));
+    let (responses, response_rx) = tokio::sync::mpsc::channel(config.max_in_flight);
+    let mut writer_task = JoinSet::new();
+    writer_task.spawn(write_frames(writer, response_rx));
+
+    let mut queries = JoinSet::new();
+    let mut idle_since = Instant::now();
+    loop {
+        tokio::select! {
+            frame = frames.recv(), if queries.len() < config.max_in_flight => {
+                let Some(frame) = frame else {
+                    break;
+                };
+                let responses = responses.clone();
+                match Message::from_bytes(&frame) {
+                    Ok(message) => {
+                        let request = Request {
+                            message,
+                            src,
+                            protocol: Protocol::Tcp,
+                            received_at: Instant::now(),
+                            raw_len: frame.len(),
+                        };
+                        let handler = Arc::clone(&handler);
+                        queries.spawn(async move {
+                            answer_tcp(handler.handle(request).await, &responses).await;
+                        });
+                    }
+                    Err(_) => {
+                        if let Some(response) = format_error(&frame) {
+                            queries.spawn(async move {
+                                answer_tcp(Response::Message(response), &responses).await;
+                            });
+                        }
+                    }
+                }
+            }
+            Some(_) = queries.join_next(), if !queries.is_empty() => {
+                idle_since = Instant::now();
+            }
+            _ = tokio::time::sleep_until((idle_since + config.idle_timeout).into()),
+                if queries.is_empty() => break,
+            _ = shutdown_requested(&mut shutdown) => break,
+        }
+    }
+
+    drop(io_tasks);
+    while queries.join_next().await.is_some() {}
+    drop(responses);
+    writer_task.join_next().await;
+}
+
+async fn read_frames(mut reader: OwnedReadHalf, frames: tokio::sync::mpsc::Sender<Vec<u8>>) {
+    while let Ok(len) = reader.read_u16().await {
+        let mut frame = vec![0u8; len as usize];
+        if reader.read_exact(&mut frame).await.is_err() || frames.send(frame).await.is_err() {
+            break;
+        }
+    }
+}
+
+async fn write_frames(
+    mut writer: OwnedWriteHalf,
+    mut frames: tokio::sync::mpsc::Receiver<Vec<u8>>,
+) {
+    while let Some(frame) = frames.recv().await {
+        if writer.write_all(&frame).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Queues the messages of a response for writing, keeping the messages of
+/// a stream in order.
+async fn answer_tcp(response: Response, responses: &tokio::sync::mpsc::Sender<Vec<u8>>) {
+    match response {
+        Response::None => {}
+        Response::Message(message) => {
+            if let Some(frame) = tcp_frame(&message) {
+                let _ = responses.send(frame).await;
+            }
+        }
+        Response::Stream(mut messages) => {
+            while let Some(message) = messages.recv().await {
+                if let Some(frame) = tcp_frame(&message) {
+                    if responses.send(frame).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A length-prefixed message. Messages too large for a frame are truncated
+/// and have TC set; if that fails too, a SERVFAIL takes their place.
+fn tcp_frame(message: &Message) -> Option<Vec<u8>> {
+    let response_buf = message
+        .to_wire_limited(u16::MAX as usize)
+        .or_else(|_| {
+            Message::response_to(message)
+                .rcode(Rcode::ServFail)
+                .build()
+                .to_bytes()
+        })
+        .ok()?;
+    let response_len = u16::try_from(response_buf.len()).ok()?;
+    let mut frame = Vec::with_capacity(2 + response_buf.len());
+    frame.extend_from_slice(&response_len.to_be_bytes());
+    frame.extend_from_slice(&response_buf);
+    Some(frame)
+}
+
+/// A FORMERR response to a query that could not be decoded, echoing its ID,
+/// opcode and RD bit. Returns `None` if not even the ID is present, or if
+/// the message is itself a response.
+fn format_error(query: &[u8]) -> Option<Message> {
+    let id = u16::from_be_bytes([*query.first()?, *query.get(1)?]);
+    let flags = query.get(2).copied().unwrap_or(0);
+    if flags & 0x80 != 0 {
+        return None;
+    }
+    let mut response = Message::new();
+    response.header.id = id;
+    response.header.qr = true;
+    response.header.opcode = Opcode::from((flags >> 3) & 0x0F);
+    response.header.rd = flags & 0x01 != 0;
+    response.set_rcode(Rcode::FormErr);
+    Some(response)
+}
+
+/// Serves the given sockets on a runtime of its own in a background thread,
+/// for the blocking entry points.
+pub(crate) fn spawn_blocking_server<H: RequestHandler>(
+    handler: H,
+    udp: Option<std::net::UdpSocket>,
+    tcp: Option<std::net::TcpListener>,
+) -> io::Result<ServerHandle> {
+    let mut local_addrs = Vec::new();
+    if let Some(socket) = &udp {
+        local_addrs.push(socket.local_addr()?);
+    }
+    if let Some(listener) = &tcp {
+        local_addrs.push(listener.local_addr()?);
+    }
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+    let (shutdown, shutdown_rx) = watch::channel(None);
+    let (done_tx, done) = oneshot::channel();
+    std::thread::spawn(move || {
+        let result = runtime.block_on(async move {
+            let mut server = Server::new(handler);
+            if let Some(socket) = udp {
+                socket.set_nonblocking(true)?;
+                server.add_udp_socket(UdpSocket::from_std(socket)?);
+            }
+            if let Some(listener) = tcp {
+                listener.set_nonblocking(true)?;
+                server.add_tcp_listener(TcpListener::from_std(listener)?);
+            }
+            server.run_until_shutdown(shutdown_rx).await
+        });
+        // Abandoned tasks are cancelled before the handle learns of the end.
+        drop(runtime);
+        report(done_tx, result);
+    });
+    Ok(ServerHandle {
+        local_addrs,
+        shutdown,
+        done,
+    })
+}
@@ -0,0 +1,155 @@
+#![cfg(feature = "tokio")]
+
+use dns_core::{Message, Name, RData, Record, RecordClass, RecordType};
+use dns_transport::{Client, ClientError, Request, Response, Server};
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use std::thread;
+use std::time::Duration;
+
+fn query() -> Message {
+    Message::query(Name::parse("example.com.", None).unwrap(), RecordType::A).build()
+}
+
+/// A response with `count` A records. A hundred of them overflow the 512
+/// bytes allowed over UDP without EDNS.
+fn answer(request: &Message, count: u8) -> Message {
+    let name = request.questions[0].qname.clone();
+    let mut builder = Message::response_to(request);
+    for i in 0..count {
+        builder = builder.answer(Record {
+            name: name.clone(),
+            rtype: RecordType::A,
+            rclass: RecordClass::IN,
+            ttl: 60,
+            rdata: RData::A(Ipv4Addr::new(192, 0, 2, i)),
+        });
+    }
+    builder.build()
+}
+
+/// Serves UDP and TCP on the same port.
+async fn start_server(count: u8) -> SocketAddr {
+    let mut server = Server::new(move |request: Request| async move {
+        Response::from(answer(&request.message, count))
+    });
+    let addr = server.bind_udp("127.0.0.1:0").await.unwrap();
+    server.bind_tcp(addr).await.unwrap();
+    tokio::spawn(server.run());
+    addr
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn blocking_and_async_queries() {
+    let addr = start_server(1).await;
+    let client = Client::new(addr);
+    let query = query();
+
+    let response = client.query_async(&query).await.unwrap();
+    assert_eq!(response.questions, query.questions);
+    assert_eq!(response.answers.len(), 1);
+    let response = client.query_tcp_async(&query).await.unwrap();
+    assert_eq!(response.answers.len(), 1);
+
+    tokio::task::spawn_blocking(move || {
+        let response = client.query(&query).unwrap();
+        assert!(response.header.qr && !response.header.tc);
+        assert_eq!(response.answers.len(), 1);
+        let response = client.query_tcp(&query).unwrap();
+        assert_eq!(response.answers.len(), 1);
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn truncated_responses_fall_back_to_tcp() {
+    let addr = start_server(100).await;
+    let client = Client::new(addr);
+    let query = query();
+
+    let response = client.query_async(&query).await.unwrap();
+    assert!(!response.header.tc);
+    assert_eq!(response.answers.len(), 100);
+
+    let truncated = client
+        .clone()
+        .tcp_fallback(false)
+        .query_udp_async(&query)
+        .await
+        .unwrap();
+    assert!(truncated.header.tc);
+    assert!(truncated.answers.len() < 100);
+
+    tokio::task::spawn_blocking(move || {
+        assert_eq!(client.query(&query).unwrap().answers.len(), 100);
+    })
+    .await
+    .unwrap();
+}
+
+#[test]
+fn retries_and_ignores_forged_responses() {
+    let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let addr = server.local_addr().unwrap();
+    let responder = thread::spawn(move || {
+        let mut buf = [0u8; 512];
+        let mut ports = Vec::new();
+
+        // Drop the first attempt.
+        let (_, src) = server.recv_from(&mut buf).unwrap();
+        ports.push(src.port());
+
+        let (len, src) = server.recv_from(&mut buf).unwrap();
+        ports.push(src.port());
+        let request = Message::from_bytes(&buf[..len]).unwrap();
+
+        let mut wrong_id = answer(&request, 1);
+        wrong_id.header.id = request.header.id.wrapping_add(1);
+        let mut wrong_question = answer(&request, 1);
+        wrong_question.questions[0].qtype = RecordType::AAAA;
+        let forger = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let forged = answer(&request, 3);
+        for (socket, response) in [
+            (&server, wrong_id),
+            (&server, wrong_question),
+            (&forger, forged),
+            (&server, answer(&request, 2)),
+        ] {
+            socket.send_to(&response.to_bytes().unwrap(), src).unwrap();
+        }
+        ports
+    });
+
+    let client = Client::new(addr).timeout(Duration::from_millis(200));
+    let query = query();
+    let response = client.query(&query).unwrap();
+    assert_eq!(response.answers.len(), 2);
+    assert_eq!(response.questions, query.questions);
+
+    let ports = responder.join().unwrap();
+    assert_ne!(ports[0], ports[1]);
+}
+
+#[test]
+fn times_out_without_response() {
+    let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let addr = server.local_addr().unwrap();
+    let client = Client::new(addr)
+        .timeout(Duration::from_millis(50))
+        .retries(2);
+    match client.query(&query()) {
+        Err(ClientError::Timeout(server)) => assert_eq!(server, addr),
+        other => panic!("unexpected result {:?}", other),
+    }
+
+    let mut buf = [0u8; 512];
+    let mut attempts = 0;
+    server
+        .set_read_timeout(Some(Duration::from_millis(100)))
+        .unwrap();
+    while let Ok((len, _)) = server.recv_from(&mut buf) {
+        assert!(Message::from_bytes(&buf[..len]).is_ok());
+        attempts += 1;
+    }
+    assert_eq!(attempts, 3);
+}
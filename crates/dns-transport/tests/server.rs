@@ -0,0 +1,407 @@
+#![cfg(feature = "tokio")]
+
+use dns_core::{Message, Name, RData, Rcode, Record, RecordClass, RecordType};
+use dns_transport::tcp_server::start_tcp_server;
+use dns_transport::udp_server::start_udp_server;
+use dns_transport::{Protocol, Request, Response, Server};
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpStream, UdpSocket};
+use std::time::{Duration, Instant};
+
+fn answer(request: &Message) -> Message {
+    let name = request.questions[0].qname.clone();
+    Message::response_to(request)
+        .answer(Record {
+            name,
+            rtype: RecordType::A,
+            rclass: RecordClass::IN,
+            ttl: 60,
+            rdata: RData::A(Ipv4Addr::new(192, 0, 2, 1)),
+        })
+        .build()
+}
+
+fn query() -> Message {
+    Message::query(Name::parse("example.com.", None).unwrap(), RecordType::A).build()
+}
+
+fn exchange_udp(addr: SocketAddr, query: &Message) -> Message {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    socket.send_to(&query.to_bytes().unwrap(), addr).unwrap();
+    let mut buf = [0u8; 512];
+    let (len, _) = socket.recv_from(&mut buf).unwrap();
+    Message::from_bytes(&buf[..len]).unwrap()
+}
+
+fn exchange_tcp(stream: &mut TcpStream, query: &Message) -> Message {
+    let bytes = query.to_bytes().unwrap();
+    stream
+        .write_all(&(bytes.len() as u16).to_be_bytes())
+        .unwrap();
+    stream.write_all(&bytes).unwrap();
+    read_tcp(stream)
+}
+
+fn read_tcp(stream: &mut TcpStream) -> Message {
+    let mut len = [0u8; 2];
+    stream.read_exact(&mut len).unwrap();
+    let mut buf = vec![0u8; u16::from_be_bytes(len) as usize];
+    stream.read_exact(&mut buf).unwrap();
+    Message::from_bytes(&buf).unwrap()
+}
+
+fn check(query: &Message, response: &Message) {
+    assert_eq!(response.header.id, query.header.id);
+    assert!(response.header.qr);
+    assert_eq!(response.answers.len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn async_server_on_several_addresses() {
+    let mut server = Server::new(|request: Request| async move {
+        tokio::task::yield_now().await;
+        assert!(request.src.ip().is_loopback());
+        assert_eq!(request.raw_len, request.message.to_bytes().unwrap().len());
+        Response::from(answer(&request.message))
+    });
+    let udp = [
+        server.bind_udp("127.0.0.1:0").await.unwrap(),
+        server.bind_udp("127.0.0.1:0").await.unwrap(),
+    ];
+    let tcp = server.bind_tcp("127.0.0.1:0").await.unwrap();
+    tokio::spawn(server.run());
+
+    tokio::task::spawn_blocking(move || {
+        for addr in udp {
+            let query = query();
+            check(&query, &exchange_udp(addr, &query));
+        }
+        let mut stream = TcpStream::connect(tcp).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        for _ in 0..3 {
+            let query = query();
+            check(&query, &exchange_tcp(&mut stream, &query));
+        }
+    })
+    .await
+    .unwrap();
+}
+
+#[test]
+fn blocking_wrappers() {
+    let handler = |request: Request| async move { Response::from(answer(&request.message)) };
+    let udp = start_udp_server("127.0.0.1:0", handler).unwrap();
+    let tcp = start_tcp_server("127.0.0.1:0", handler).unwrap();
+
+    let query = query();
+    check(&query, &exchange_udp(udp.local_addr(), &query));
+    let mut stream = TcpStream::connect(tcp.local_addr()).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    check(&query, &exchange_tcp(&mut stream, &query));
+
+    for handle in [udp, tcp] {
+        handle.shutdown(Duration::from_secs(1));
+        handle.join().unwrap();
+    }
+    // Idle connections are closed on shutdown.
+    let mut buf = [0u8; 1];
+    assert_eq!(stream.read(&mut buf).unwrap(), 0);
+}
+
+#[test]
+fn shutdown_finishes_in_flight_queries() {
+    let handler = |request: Request| async move {
+        let delay = match request.message.questions[0].qtype {
+            RecordType::A => Duration::from_millis(200),
+            _ => Duration::from_secs(30),
+        };
+        tokio::time::sleep(delay).await;
+        Response::from(answer(&request.message))
+    };
+    let handle = start_tcp_server("127.0.0.1:0", handler).unwrap();
+    let addr = handle.local_addr();
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let query = query();
+    let bytes = query.to_bytes().unwrap();
+    stream
+        .write_all(&(bytes.len() as u16).to_be_bytes())
+        .unwrap();
+    stream.write_all(&bytes).unwrap();
+    std::thread::sleep(Duration::from_millis(50));
+
+    handle.shutdown(Duration::from_secs(5));
+    check(&query, &read_tcp(&mut stream));
+    handle.join().unwrap();
+    assert!(TcpStream::connect(addr).is_err());
+
+    // Queries still running at the deadline are abandoned.
+    let handle = start_udp_server("127.0.0.1:0", handler).unwrap();
+    let slow = Message::query(Name::root(), RecordType::NS).build();
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket
+        .send_to(&slow.to_bytes().unwrap(), handle.local_addr())
+        .unwrap();
+    std::thread::sleep(Duration::from_millis(50));
+    let started = Instant::now();
+    handle.shutdown(Duration::from_millis(100));
+    handle.join().unwrap();
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn start_on_current_runtime() {
+    let mut server =
+        Server::new(|request: Request| async move { Response::from(answer(&request.message)) });
+    assert!(Server::new(|_: Request| async { Response::None })
+        .start()
+        .is_err());
+    server.bind_udp("127.0.0.1:0").await.unwrap();
+    server.bind_tcp("127.0.0.1:0").await.unwrap();
+    let handle = server.start().unwrap();
+    let [udp, tcp] = handle.local_addrs() else {
+        panic!("expected two addresses");
+    };
+    let (udp, tcp) = (*udp, *tcp);
+
+    tokio::task::spawn_blocking(move || {
+        let query = query();
+        check(&query, &exchange_udp(udp, &query));
+        let mut stream = TcpStream::connect(tcp).unwrap();
+        check(&query, &exchange_tcp(&mut stream, &query));
+    })
+    .await
+    .unwrap();
+
+    // A current-thread runtime keeps serving while the test awaits.
+    handle.shutdown(Duration::ZERO);
+    handle.stopped().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn streamed_and_dropped_responses() {
+    let mut server = Server::new(|request: Request| async move {
+        match (request.protocol, request.message.questions[0].qtype) {
+            (Protocol::Udp, RecordType::AXFR) => Response::None,
+            (Protocol::Tcp, RecordType::AXFR) => {
+                let (sender, response) = Response::channel(1);
+                tokio::spawn(async move {
+                    for _ in 0..3 {
+                        sender.send(answer(&request.message)).await.unwrap();
+                    }
+                });
+                response
+            }
+            _ => Response::from_messages(vec![answer(&request.message), answer(&request.message)]),
+        }
+    });
+    let udp = server.bind_udp("127.0.0.1:0").await.unwrap();
+    let tcp = server.bind_tcp("127.0.0.1:0").await.unwrap();
+    tokio::spawn(server.run());
+
+    tokio::task::spawn_blocking(move || {
+        let name = Name::parse("example.com.", None).unwrap();
+        let axfr = Message::query(name, RecordType::AXFR).build();
+        let mut stream = TcpStream::connect(tcp).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let first = exchange_tcp(&mut stream, &axfr);
+        check(&axfr, &first);
+        for _ in 0..2 {
+            check(&axfr, &read_tcp(&mut stream));
+        }
+
+        // Only the first message of a stream goes out over UDP.
+        let query = query();
+        check(&query, &exchange_udp(udp, &query));
+
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket
+            .set_read_timeout(Some(Duration::from_millis(200)))
+            .unwrap();
+        socket.send_to(&axfr.to_bytes().unwrap(), udp).unwrap();
+        let mut buf = [0u8; 512];
+        assert!(socket.recv_from(&mut buf).is_err());
+    })
+    .await
+    .unwrap();
+}
+
+fn write_query(stream: &mut TcpStream, bytes: &[u8]) {
+    let mut frame = (bytes.len() as u16).to_be_bytes().to_vec();
+    frame.extend_from_slice(bytes);
+    stream.write_all(&frame).unwrap();
+}
+
+/// Answers A queries after 300 ms and everything else at once.
+async fn slow_a(request: Request) -> Response {
+    if request.message.questions[0].qtype == RecordType::A {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+    }
+    Response::from(answer(&request.message))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pipelined_queries_answered_out_of_order() {
+    let mut server = Server::new(slow_a);
+    let addr = server.bind_tcp("127.0.0.1:0").await.unwrap();
+    tokio::spawn(server.run());
+    let mut limited = Server::new(slow_a).tcp_max_in_flight(1);
+    let limited_addr = limited.bind_tcp("127.0.0.1:0").await.unwrap();
+    tokio::spawn(limited.run());
+
+    tokio::task::spawn_blocking(move || {
+        let name = Name::parse("example.com.", None).unwrap();
+        let slow = Message::query(name.clone(), RecordType::A).build();
+        let fast = Message::query(name, RecordType::AAAA).build();
+        for (addr, expected) in [(addr, [&fast, &slow]), (limited_addr, [&slow, &fast])] {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+            write_query(&mut stream, &slow.to_bytes().unwrap());
+            write_query(&mut stream, &fast.to_bytes().unwrap());
+            for query in expected {
+                check(query, &read_tcp(&mut stream));
+            }
+        }
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn udp_skips_responses_and_limits_queries_in_flight() {
+    let mut server = Server::new(slow_a).udp_max_in_flight(1);
+    let addr = server.bind_udp("127.0.0.1:0").await.unwrap();
+    tokio::spawn(server.run());
+
+    tokio::task::spawn_blocking(move || {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let name = Name::parse("example.com.", None).unwrap();
+        let mut response = Message::query(name.clone(), RecordType::AAAA).id(1).build();
+        response.header.qr = true;
+        let slow = Message::query(name.clone(), RecordType::A).id(2).build();
+        let fast = Message::query(name, RecordType::AAAA).id(3).build();
+        for message in [&response, &slow, &fast] {
+            socket.send_to(&message.to_bytes().unwrap(), addr).unwrap();
+        }
+
+        // The fast query waits for the slow one, and the response gets no
+        // answer.
+        let mut buf = [0u8; 512];
+        let mut ids = Vec::new();
+        for _ in 0..2 {
+            let (len, _) = socket.recv_from(&mut buf).unwrap();
+            ids.push(Message::from_bytes(&buf[..len]).unwrap().header.id);
+        }
+        assert_eq!(ids, [2, 3]);
+        socket
+            .set_read_timeout(Some(Duration::from_millis(100)))
+            .unwrap();
+        assert!(socket.recv_from(&mut buf).is_err());
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn malformed_queries_get_formerr() {
+    let mut server = Server::new(slow_a);
+    let addr = server.bind_tcp("127.0.0.1:0").await.unwrap();
+    tokio::spawn(server.run());
+
+    tokio::task::spawn_blocking(move || {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        // A header announcing a question that is missing.
+        write_query(&mut stream, &[0x12, 0x34, 0x01, 0, 0, 1, 0, 0, 0, 0, 0, 0]);
+        let response = read_tcp(&mut stream);
+        assert_eq!(response.header.id, 0x1234);
+        assert!(response.header.qr && response.header.rd);
+        assert_eq!(response.rcode(), Rcode::FormErr);
+        assert!(response.questions.is_empty());
+
+        // The connection stays usable.
+        let query = query();
+        check(&query, &exchange_tcp(&mut stream, &query));
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn oversized_tcp_responses_are_truncated() {
+    let mut server = Server::new(|request: Request| async move {
+        // About 78 KB of answers in separate RRsets, more than a TCP frame
+        // can carry.
+        let records = (0..300).map(|i| Record {
+            name: Name::parse(&format!("r{}.example.com.", i), None).unwrap(),
+            rtype: RecordType::TXT,
+            rclass: RecordClass::IN,
+            ttl: 60,
+            rdata: RData::TXT(vec![vec![b'x'; 255]]),
+        });
+        Response::from(
+            Message::response_to(&request.message)
+                .answers(records)
+                .build(),
+        )
+    });
+    let addr = server.bind_tcp("127.0.0.1:0").await.unwrap();
+    tokio::spawn(server.run());
+
+    tokio::task::spawn_blocking(move || {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let query = query();
+        let response = exchange_tcp(&mut stream, &query);
+        assert_eq!(response.header.id, query.header.id);
+        assert!(response.header.tc);
+        assert!(!response.answers.is_empty() && response.answers.len() < 300);
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn idle_connections_are_closed() {
+    let mut server = Server::new(slow_a).tcp_idle_timeout(Duration::from_millis(100));
+    let addr = server.bind_tcp("127.0.0.1:0").await.unwrap();
+    tokio::spawn(server.run());
+
+    tokio::task::spawn_blocking(move || {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        // A query outlasting the idle timeout keeps the connection open.
+        let query = query();
+        check(&query, &exchange_tcp(&mut stream, &query));
+
+        let started = Instant::now();
+        let mut buf = [0u8; 1];
+        assert_eq!(stream.read(&mut buf).unwrap(), 0);
+        assert!(started.elapsed() < Duration::from_secs(2));
+    })
+    .await
+    .unwrap();
+}
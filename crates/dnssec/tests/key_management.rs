@@ -0,0 +1,22 @@
+use dns_core::dnssec::Dnskey;
+use dnssec::key_management::{DNSSECKey, KeyType};
+use ring::signature::{Ed25519KeyPair, KeyPair};
+
+#[test]
+fn private_key_matches_public_key() {
+    let key = DNSSECKey::generate(KeyType::Ed25519).unwrap();
+    let key_pair = Ed25519KeyPair::from_pkcs8(&key.private_key).unwrap();
+    assert_eq!(key_pair.public_key().as_ref(), &key.public_key[..]);
+}
+
+#[test]
+fn key_tag_covers_published_flags() {
+    let zsk = DNSSECKey::generate(KeyType::Ed25519).unwrap();
+    assert_eq!(zsk.dnskey().flags, Dnskey::ZONE_KEY);
+    assert_eq!(zsk.dnskey().key_tag(), zsk.key_tag);
+
+    let flags = Dnskey::ZONE_KEY | Dnskey::SECURE_ENTRY_POINT;
+    let ksk = DNSSECKey::generate_with_flags(KeyType::Ed25519, flags).unwrap();
+    assert!(ksk.dnskey().is_secure_entry_point());
+    assert_eq!(ksk.dnskey().key_tag(), ksk.key_tag);
+}
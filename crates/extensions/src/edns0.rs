@@ -1,6 +1,6 @@
-use std::{error::Error as Errr, io::Read};
+use std::error::Error as Errr;
 
-use dns_core::{message::Message, RData, Record};
+use dns_core::{message::Message, Header, Name, RData, Rcode, Record, RecordClass, RecordType};
 use serde::ser::SerializeStruct;
 use serde::{
     de::{MapAccess, Visitor},
@@ -117,6 +117,12 @@ impl Clone for EDNS0 {
         }
     }
 }
+impl Default for EDNS0 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl EDNS0 {
     pub fn new() -> Self {
         EDNS0 {
@@ -130,43 +136,38 @@ impl EDNS0 {
 
     pub fn parse(message: &Message) -> Result<Option<Self>, EDNS0Error> {
         for additional in &message.additionals {
-            if additional.rtype == 41 {
-  
+            if additional.rtype == RecordType::OPT {
                 if let RData::Raw(data) = &additional.rdata {
-                    let mut reader = &data[..];
-                    let mut edns0 = EDNS0::new();
-                    edns0.version = read_u8(&mut reader)?;
-                    edns0.flags = read_u16(&mut reader)?;
-                    edns0.udp_size = read_u16(&mut reader)?;
-                    edns0.ext_rcode = read_u8(&mut reader)?;
-                    let rdlength = data.len() - 6;
-                    if rdlength > 0 {
-                        let mut edns0_data = vec![0u8; rdlength];
-                        reader.read_exact(&mut edns0_data)?;
-                        edns0.edns0_data = edns0_data;
-                    }
-                    return Ok(Some(edns0));
+                    // RFC 6891: the class carries the payload size and the TTL
+                    // carries the extended RCODE, version and flags.
+                    return Ok(Some(EDNS0 {
+                        version: (additional.ttl >> 16) as u8,
+                        flags: additional.ttl as u16,
+                        udp_size: u16::from(additional.rclass),
+                        ext_rcode: (additional.ttl >> 24) as u8,
+                        edns0_data: data.clone(),
+                    }));
                 }
+                return Err(EDNS0Error::InvalidRecord);
             }
         }
         Ok(None)
     }
 
-    pub fn add_to_message(&self, message: &mut Message) -> Result<(), Box<dyn Errr>> {
-        let mut data = Vec::with_capacity(6 + self.edns0_data.len());
-        data.push(self.version);
-        data.extend_from_slice(&self.flags.to_be_bytes());
-        data.extend_from_slice(&self.udp_size.to_be_bytes());
-        data.push(self.ext_rcode);
-        data.extend_from_slice(&self.edns0_data);
+    /// The full response code of `header` extended with this record's bits.
+    pub fn rcode(&self, header: &Header) -> Rcode {
+        Rcode::from_parts(header.rcode.header_bits(), self.ext_rcode)
+    }
 
-        message.write(&mut data)?;
+    pub fn add_to_message(&self, message: &mut Message) -> Result<(), Box<dyn Errr>> {
         let opt_record = Record {
-            name: ".".to_string(),
-            rtype: 41,
-            rclass: self.udp_size,
-            ttl: ((self.flags as u32) << 16) | (self.ext_rcode as u32),
-            rdata: RData::Raw(data.to_vec()),
+            name: Name::root(),
+            rtype: RecordType::OPT,
+            rclass: RecordClass::from(self.udp_size),
+            ttl: ((self.ext_rcode as u32) << 24)
+                | ((self.version as u32) << 16)
+                | self.flags as u32,
+            rdata: RData::Raw(self.edns0_data.clone()),
         };
         message.additionals.push(opt_record);
         Ok(())
@@ -179,16 +180,3 @@ pub enum EDNS0Error {
     #[error("Invalid EDNS0 record")]
     InvalidRecord,
 }
-
-//todo move this to helper
-fn read_u8<R: Read>(reader: &mut R) -> std::io::Result<u8> {
-    let mut buf = [0u8; 1];
-    reader.read_exact(&mut buf)?;
-    Ok(buf[0])
-}
-
-fn read_u16<R: Read>(reader: &mut R) -> std::io::Result<u16> {
-    let mut buf = [0u8; 2];
-    reader.read_exact(&mut buf)?;
-    Ok(u16::from_be_bytes(buf))
-}
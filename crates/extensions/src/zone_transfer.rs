@@ -1,6 +1,6 @@
-
 use dns_core::message::Message;
 use dns_core::record::Record;
+use dns_core::types::RecordType;
 use std::io;
 use thiserror::Error;
 
@@ -14,19 +14,11 @@ pub enum ZoneTransferError {
     InvalidIxfrRequest,
 }
 pub fn handle_axfr_rf(request: &Message, zone_data: &[Record]) -> io::Result<Message> {
-    let mut response = Message::new();
-    response.header.id = request.header.id;
-    response.header.qr = true;
-    response.header.aa = true;
-    response.header.rd = request.header.rd;
-    response.header.ra = true;
-    response.header.rcode = 0;
-
-    response.questions = request.questions.clone();
-    response.answers = zone_data.to_vec();
-    response.header.ancount = response.answers.len() as u16;
-
-    Ok(response)
+    Ok(Message::response_to(request)
+        .aa(true)
+        .ra(true)
+        .answers(zone_data.iter().cloned())
+        .build())
 }
 
 pub fn handle_ixfr(
@@ -38,22 +30,13 @@ pub fn handle_ixfr(
     }
 
     let question = &request.questions[0];
-    if question.qtype != 251 {
-
+    if question.qtype != RecordType::IXFR {
         return Err(ZoneTransferError::InvalidIxfrRequest);
     }
 
-    let mut response = Message::new();
-    response.header.id = request.header.id;
-    response.header.qr = true;
-    response.header.aa = true;
-    response.header.rd = request.header.rd;
-    response.header.ra = true;
-    response.header.rcode = 0;
-
-    response.questions.push(question.clone());
-    response.answers.extend_from_slice(incremental_changes);
-    response.header.ancount = response.answers.len() as u16;
-
-    Ok(response)
+    Ok(Message::response_to(request)
+        .aa(true)
+        .ra(true)
+        .answers(incremental_changes.iter().cloned())
+        .build())
 }
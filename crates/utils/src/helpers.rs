@@ -1,4 +1,16 @@
-use std::net::{Ipv4Addr, Ipv6Addr};
+use dns_core::Name;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ReverseError {
+    #[error("Invalid CIDR {0}")]
+    InvalidCidr(String),
+    #[error("Prefix length {0} is out of range")]
+    InvalidPrefix(u8),
+    #[error("Classless delegation requires a prefix length between 25 and 31, got {0}")]
+    NotClassless(u8),
+}
 
 pub fn ipv4_to_bytes(ip: &Ipv4Addr) -> [u8; 4] {
     ip.octets()
@@ -7,3 +19,189 @@ pub fn ipv4_to_bytes(ip: &Ipv4Addr) -> [u8; 4] {
 pub fn ipv6_to_bytes(ip: &Ipv6Addr) -> [u8; 16] {
     ip.octets()
 }
+
+/// The PTR owner name of an address, e.g. `2.2.0.192.in-addr.arpa.` for
+/// 192.0.2.2, or the 32 nibble labels under `ip6.arpa.` for IPv6.
+pub fn reverse_name(ip: IpAddr) -> Name {
+    match ip {
+        IpAddr::V4(ip) => v4_zone(&ip.octets()),
+        IpAddr::V6(ip) => v6_zone(&nibbles(&ip), 32),
+    }
+}
+
+/// The address a reverse name stands for. Names inside an RFC 2317
+/// classless zone, such as `5.0/25.2.0.192.in-addr.arpa.`, are accepted.
+/// Returns `None` for anything that is not a complete reverse name.
+pub fn ip_from_reverse_name(name: &Name) -> Option<IpAddr> {
+    let labels: Vec<&[u8]> = name.labels().collect();
+    let (rest, suffix) = labels.split_at(labels.len().checked_sub(2)?);
+    if suffix[1].eq_ignore_ascii_case(b"arpa") && suffix[0].eq_ignore_ascii_case(b"in-addr") {
+        let octets: Vec<&[u8]> = match rest.len() {
+            4 => rest.to_vec(),
+            5 if is_classless_label(rest[1]) => [&rest[..1], &rest[2..]].concat(),
+            _ => return None,
+        };
+        let mut addr = [0u8; 4];
+        for (octet, label) in addr.iter_mut().zip(octets.iter().rev()) {
+            *octet = decimal_octet(label)?;
+        }
+        Some(IpAddr::V4(Ipv4Addr::from(addr)))
+    } else if suffix[1].eq_ignore_ascii_case(b"arpa")
+        && suffix[0].eq_ignore_ascii_case(b"ip6")
+        && rest.len() == 32
+    {
+        let mut addr = [0u8; 16];
+        for (i, label) in rest.iter().rev().enumerate() {
+            let nibble = match label {
+                [c] => (*c as char).to_digit(16)? as u8,
+                _ => return None,
+            };
+            addr[i / 2] |= if i % 2 == 0 { nibble << 4 } else { nibble };
+        }
+        Some(IpAddr::V6(Ipv6Addr::from(addr)))
+    } else {
+        None
+    }
+}
+
+/// The zone an RFC 2317 classless delegation of a /25 to /31 is made to,
+/// named `<first>/<prefix>` under the enclosing /24, e.g.
+/// `0/25.2.0.192.in-addr.arpa.`. Host bits of `network` are ignored.
+pub fn classless_zone(network: Ipv4Addr, prefix_len: u8) -> Result<Name, ReverseError> {
+    if !(25..=31).contains(&prefix_len) {
+        return Err(ReverseError::NotClassless(prefix_len));
+    }
+    let [a, b, c, d] = network.octets();
+    let first = d & (0xFF << (32 - prefix_len));
+    let mut labels = vec![format!("{}/{}", first, prefix_len).into_bytes()];
+    labels.extend([c, b, a].iter().map(|octet| octet.to_string().into_bytes()));
+    labels.extend([b"in-addr".to_vec(), b"arpa".to_vec()]);
+    Ok(Name::from_labels(labels).expect("reverse names are always valid"))
+}
+
+/// The name inside a classless zone that the CNAME at the ordinary
+/// reverse name of `ip` points to, e.g. `5.0/25.2.0.192.in-addr.arpa.`.
+pub fn classless_name(ip: Ipv4Addr, prefix_len: u8) -> Result<Name, ReverseError> {
+    let zone = classless_zone(ip, prefix_len)?;
+    let host = ip.octets()[3].to_string();
+    Ok(
+        Name::from_labels(std::iter::once(host.as_bytes()).chain(zone.labels()))
+            .expect("reverse names are always valid"),
+    )
+}
+
+/// Parses `address/prefix`. A bare address is taken as a single host.
+pub fn parse_cidr(s: &str) -> Result<(IpAddr, u8), ReverseError> {
+    let invalid = || ReverseError::InvalidCidr(s.to_string());
+    let (addr, prefix_len) = match s.split_once('/') {
+        Some((addr, prefix)) => {
+            let addr: IpAddr = addr.parse().map_err(|_| invalid())?;
+            (addr, prefix.parse().map_err(|_| invalid())?)
+        }
+        None => {
+            let addr: IpAddr = s.parse().map_err(|_| invalid())?;
+            (addr, if addr.is_ipv4() { 32 } else { 128 })
+        }
+    };
+    let max = if addr.is_ipv4() { 32 } else { 128 };
+    if prefix_len > max {
+        return Err(ReverseError::InvalidPrefix(prefix_len));
+    }
+    Ok((addr, prefix_len))
+}
+
+/// The reverse zones covering a network. Prefixes on an octet (IPv4) or
+/// nibble (IPv6) boundary map to a single zone; others are split into the
+/// zones at the next boundary, e.g. a /22 into four /24 zones. IPv4
+/// prefixes of 25 to 31 bits give the RFC 2317 classless zone instead.
+pub fn reverse_zones(network: IpAddr, prefix_len: u8) -> Result<Vec<Name>, ReverseError> {
+    match network {
+        IpAddr::V4(network) => {
+            if prefix_len > 32 {
+                return Err(ReverseError::InvalidPrefix(prefix_len));
+            }
+            if (25..=31).contains(&prefix_len) {
+                return Ok(vec![classless_zone(network, prefix_len)?]);
+            }
+            let octets = network.octets();
+            let depth = (prefix_len as usize).div_ceil(8);
+            let spare = (depth * 8) as u8 - prefix_len;
+            Ok(expand(&octets[..depth], spare)
+                .iter()
+                .map(|zone| v4_zone(zone))
+                .collect())
+        }
+        IpAddr::V6(network) => {
+            if prefix_len > 128 {
+                return Err(ReverseError::InvalidPrefix(prefix_len));
+            }
+            let nibbles = nibbles(&network);
+            let depth = (prefix_len as usize).div_ceil(4);
+            let spare = (depth * 4) as u8 - prefix_len;
+            Ok(expand(&nibbles[..depth], spare)
+                .iter()
+                .map(|zone| v6_zone(zone, depth))
+                .collect())
+        }
+    }
+}
+
+/// All values of `digits` with its last `spare` bits free, which are the
+/// host bits of a prefix that ends inside the last digit.
+fn expand(digits: &[u8], spare: u8) -> Vec<Vec<u8>> {
+    let Some((&last, prefix)) = digits.split_last() else {
+        return vec![Vec::new()];
+    };
+    let base = last & (0xFF << spare);
+    (0..1u16 << spare)
+        .map(|offset| {
+            let mut zone = prefix.to_vec();
+            zone.push(base + offset as u8);
+            zone
+        })
+        .collect()
+}
+
+fn v4_zone(octets: &[u8]) -> Name {
+    let mut labels: Vec<Vec<u8>> = octets
+        .iter()
+        .rev()
+        .map(|octet| octet.to_string().into_bytes())
+        .collect();
+    labels.extend([b"in-addr".to_vec(), b"arpa".to_vec()]);
+    Name::from_labels(labels).expect("reverse names are always valid")
+}
+
+fn v6_zone(nibbles: &[u8], depth: usize) -> Name {
+    let mut labels: Vec<Vec<u8>> = nibbles[..depth]
+        .iter()
+        .rev()
+        .map(|nibble| format!("{:x}", nibble).into_bytes())
+        .collect();
+    labels.extend([b"ip6".to_vec(), b"arpa".to_vec()]);
+    Name::from_labels(labels).expect("reverse names are always valid")
+}
+
+fn nibbles(ip: &Ipv6Addr) -> [u8; 32] {
+    let mut nibbles = [0u8; 32];
+    for (i, byte) in ip.octets().iter().enumerate() {
+        nibbles[2 * i] = byte >> 4;
+        nibbles[2 * i + 1] = byte & 0x0F;
+    }
+    nibbles
+}
+
+fn decimal_octet(label: &[u8]) -> Option<u8> {
+    if label.is_empty() || label.len() > 3 || !label.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    std::str::from_utf8(label).ok()?.parse().ok()
+}
+
+/// Labels like `0/25` or `0-127` naming a classless delegation.
+fn is_classless_label(label: &[u8]) -> bool {
+    label.iter().any(|&c| c == b'/' || c == b'-')
+        && label
+            .iter()
+            .all(|&c| c.is_ascii_digit() || c == b'/' || c == b'-')
+}
@@ -0,0 +1,112 @@
+use dns_core::Name;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use utils::helpers::{
+    classless_name, classless_zone, ip_from_reverse_name, parse_cidr, reverse_name, reverse_zones,
+    ReverseError,
+};
+
+fn name(s: &str) -> Name {
+    Name::parse(s, None).unwrap()
+}
+
+fn zones(cidr: &str) -> Vec<String> {
+    let (addr, prefix_len) = parse_cidr(cidr).unwrap();
+    reverse_zones(addr, prefix_len)
+        .unwrap()
+        .iter()
+        .map(Name::to_string)
+        .collect()
+}
+
+#[test]
+fn address_round_trip() {
+    let v4 = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 5));
+    assert_eq!(reverse_name(v4), name("5.2.0.192.in-addr.arpa."));
+    assert_eq!(ip_from_reverse_name(&reverse_name(v4)), Some(v4));
+
+    let v6: IpAddr = "2001:db8::567:89ab".parse().unwrap();
+    assert_eq!(
+        reverse_name(v6),
+        name("b.a.9.8.7.6.5.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa.")
+    );
+    assert_eq!(ip_from_reverse_name(&reverse_name(v6)), Some(v6));
+    assert_eq!(
+        ip_from_reverse_name(&name(
+            "B.A.9.8.7.6.5.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.B.D.0.1.0.0.2.IP6.ARPA."
+        )),
+        Some(v6)
+    );
+}
+
+#[test]
+fn rejects_partial_names() {
+    for s in [
+        "2.0.192.in-addr.arpa.",
+        "256.2.0.192.in-addr.arpa.",
+        "x.2.0.192.in-addr.arpa.",
+        "5.2.0.192.in-addr.example.",
+        "0.1.0.0.2.ip6.arpa.",
+        "arpa.",
+        ".",
+    ] {
+        assert_eq!(ip_from_reverse_name(&name(s)), None, "{}", s);
+    }
+}
+
+#[test]
+fn classless_delegation() {
+    let ip = Ipv4Addr::new(192, 0, 2, 130);
+    assert_eq!(
+        classless_zone(ip, 25).unwrap(),
+        name("128/25.2.0.192.in-addr.arpa.")
+    );
+    let target = classless_name(ip, 26).unwrap();
+    assert_eq!(target, name("130.128/26.2.0.192.in-addr.arpa."));
+    assert_eq!(ip_from_reverse_name(&target), Some(IpAddr::V4(ip)));
+    assert_eq!(
+        ip_from_reverse_name(&name("5.0-127.2.0.192.in-addr.arpa.")),
+        Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 5)))
+    );
+    assert_eq!(classless_zone(ip, 24), Err(ReverseError::NotClassless(24)));
+}
+
+#[test]
+fn cidr_to_zones() {
+    assert_eq!(zones("192.0.2.0/24"), ["2.0.192.in-addr.arpa."]);
+    assert_eq!(zones("10.0.0.0/8"), ["10.in-addr.arpa."]);
+    assert_eq!(zones("0.0.0.0/0"), ["in-addr.arpa."]);
+    assert_eq!(
+        zones("198.51.101.7/22"),
+        [
+            "100.51.198.in-addr.arpa.",
+            "101.51.198.in-addr.arpa.",
+            "102.51.198.in-addr.arpa.",
+            "103.51.198.in-addr.arpa.",
+        ]
+    );
+    assert_eq!(zones("192.0.2.64/27"), ["64/27.2.0.192.in-addr.arpa."]);
+    assert_eq!(zones("192.0.2.1"), ["1.2.0.192.in-addr.arpa."]);
+    assert_eq!(zones("2001:db8::/32"), ["8.b.d.0.1.0.0.2.ip6.arpa."]);
+    assert_eq!(
+        zones("2001:db8::/31"),
+        ["8.b.d.0.1.0.0.2.ip6.arpa.", "9.b.d.0.1.0.0.2.ip6.arpa."]
+    );
+    assert_eq!(zones("2001:db8::/29").len(), 8);
+    assert_eq!(
+        reverse_zones(IpAddr::V6(Ipv6Addr::LOCALHOST), 128).unwrap(),
+        [reverse_name(IpAddr::V6(Ipv6Addr::LOCALHOST))]
+    );
+}
+
+#[test]
+fn invalid_cidr() {
+    assert_eq!(
+        parse_cidr("192.0.2.0/33"),
+        Err(ReverseError::InvalidPrefix(33))
+    );
+    assert!(matches!(
+        parse_cidr("192.0.2/24"),
+        Err(ReverseError::InvalidCidr(_))
+    ));
+    assert!(reverse_zones(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 40).is_err());
+}
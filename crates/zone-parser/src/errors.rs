@@ -3,24 +3,26 @@ use std::fmt;
 #[derive(Debug)]
 pub enum ZoneParserError {
     IoError(std::io::Error),
-    RegexError(regex::Error),
     InvalidDirective(String),
     UnknownDirective(String),
     InvalidRecord(String),
     Utf8Error(std::string::FromUtf8Error),
     ParseIntError(std::num::ParseIntError),
+    InvalidName(dns_core::NameError),
+    InvalidSvcParams(dns_core::SvcbError),
 }
 
 impl fmt::Display for ZoneParserError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ZoneParserError::IoError(e) => write!(f, "IO Error: {}", e),
-            ZoneParserError::RegexError(e) => write!(f, "Regex Error: {}", e),
             ZoneParserError::InvalidDirective(e) => write!(f, "Invalid Directive: {}", e),
             ZoneParserError::UnknownDirective(e) => write!(f, "Unknown Directive: {}", e),
             ZoneParserError::InvalidRecord(e) => write!(f, "Invalid Record: {}", e),
             ZoneParserError::Utf8Error(e) => write!(f, "UTF-8 Error: {}", e),
             ZoneParserError::ParseIntError(e) => write!(f, "Parse Int Error: {}", e),
+            ZoneParserError::InvalidName(e) => write!(f, "Invalid Name: {}", e),
+            ZoneParserError::InvalidSvcParams(e) => write!(f, "Invalid SvcParams: {}", e),
         }
     }
 }
@@ -31,12 +33,6 @@ impl From<std::io::Error> for ZoneParserError {
     }
 }
 
-impl From<regex::Error> for ZoneParserError {
-    fn from(error: regex::Error) -> Self {
-        ZoneParserError::RegexError(error)
-    }
-}
-
 impl From<std::string::FromUtf8Error> for ZoneParserError {
     fn from(error: std::string::FromUtf8Error) -> Self {
         ZoneParserError::Utf8Error(error)
@@ -48,3 +44,25 @@ impl From<std::num::ParseIntError> for ZoneParserError {
         ZoneParserError::ParseIntError(error)
     }
 }
+
+impl From<dns_core::NameError> for ZoneParserError {
+    fn from(error: dns_core::NameError) -> Self {
+        ZoneParserError::InvalidName(error)
+    }
+}
+
+impl From<dns_core::SvcbError> for ZoneParserError {
+    fn from(error: dns_core::SvcbError) -> Self {
+        ZoneParserError::InvalidSvcParams(error)
+    }
+}
+
+impl From<dns_core::ParseError> for ZoneParserError {
+    fn from(error: dns_core::ParseError) -> Self {
+        match error {
+            dns_core::ParseError::Invalid(e) => ZoneParserError::InvalidRecord(e),
+            dns_core::ParseError::Name(e) => ZoneParserError::InvalidName(e),
+            dns_core::ParseError::Svcb(e) => ZoneParserError::InvalidSvcParams(e),
+        }
+    }
+}
@@ -1,8 +1,10 @@
 pub mod errors;
 pub mod parser;
+pub mod tokenizer;
 
 use crate::errors::ZoneParserError;
 use dns_core::record::Record;
+use dns_core::rrset::RRset;
 use parser::ZoneParser;
 
 pub struct ZoneFile {
@@ -15,4 +17,9 @@ impl ZoneFile {
         let records = parser.parse_zone_file(file_path)?;
         Ok(ZoneFile { records })
     }
+
+    /// The records grouped into RRsets, in order of first appearance.
+    pub fn rrsets(&self) -> Vec<RRset> {
+        RRset::from_records(self.records.iter().cloned())
+    }
 }
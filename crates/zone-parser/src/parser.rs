@@ -1,188 +1,270 @@
 // zone-parser/src/parser.rs
 
 use crate::errors::ZoneParserError;
-use dns_core::record::{RData, Record};
-use regex::Regex;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use crate::tokenizer::{tokenize, Token};
+use dns_core::name::Name;
+use dns_core::record::Record;
+use dns_core::text::{self, parse_ttl};
+use std::fs;
 use std::path::Path;
 
 pub struct ZoneParser {
-    origin: String,
+    origin: Name,
     ttl: u32,
+    last_owner: Option<Name>,
+}
+
+impl Default for ZoneParser {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ZoneParser {
     pub fn new() -> Self {
         ZoneParser {
-            origin: String::new(),
+            origin: Name::root(),
             ttl: 3600, // Default TTL
+            last_owner: None,
         }
     }
 
     pub fn parse_zone_file(&mut self, file_path: &str) -> Result<Vec<Record>, ZoneParserError> {
-        let file = File::open(file_path)?;
-        let reader = BufReader::new(file);
-        let mut records = Vec::new();
+        let contents = fs::read_to_string(file_path)?;
+        self.parse_zone(&contents, Path::new(file_path))
+    }
 
-        for line in reader.lines() {
-            let line = line?;
-            let line = line.trim();
+    /// Parses master file text. `$INCLUDE` paths are resolved relative to
+    /// the current directory.
+    pub fn parse_str(&mut self, contents: &str) -> Result<Vec<Record>, ZoneParserError> {
+        self.parse_zone(contents, Path::new("."))
+    }
 
-            // Skip comments and empty lines
-            if line.is_empty() || line.starts_with(';') {
-                continue;
-            }
+    /// Parses master file entries, joining lines grouped by parentheses and
+    /// giving lines that start with whitespace the previous owner name.
+    fn parse_zone(&mut self, contents: &str, path: &Path) -> Result<Vec<Record>, ZoneParserError> {
+        let mut records = Vec::new();
+        let mut entry: Vec<Token> = Vec::new();
+        let mut depth = 0usize;
+        let mut inherits_owner = false;
 
-            if line.starts_with('$') {
-                self.handle_directive(line, Path::new(file_path))?;
+        for line in contents.lines() {
+            let tokens = tokenize(line)?;
+            if depth == 0 {
+                if tokens.is_empty() {
+                    continue;
+                }
+                inherits_owner = line.starts_with([' ', '\t']);
+            }
+            for token in tokens {
+                match (token.quoted, token.text) {
+                    (false, "(") => depth += 1,
+                    (false, ")") => {
+                        depth = depth.checked_sub(1).ok_or_else(|| {
+                            ZoneParserError::InvalidRecord(format!("Unbalanced ')': {}", line))
+                        })?
+                    }
+                    _ => entry.push(token),
+                }
+            }
+            if depth > 0 || entry.is_empty() {
                 continue;
             }
 
-      
-            let record = self.parse_record(line)?;
-            records.push(record);
+            if !inherits_owner && entry[0].text.starts_with('$') && !entry[0].quoted {
+                records.extend(self.handle_directive(&entry, path)?);
+            } else {
+                let owner = if inherits_owner {
+                    Some(self.last_owner.clone().ok_or_else(|| {
+                        ZoneParserError::InvalidRecord(format!("No previous owner name: {}", line))
+                    })?)
+                } else {
+                    None
+                };
+                let record = self.parse_entry(owner, &entry)?;
+                self.last_owner = Some(record.name.clone());
+                records.push(record);
+            }
+            entry.clear();
+        }
+        if depth > 0 {
+            return Err(ZoneParserError::InvalidRecord(
+                "Unbalanced '(' at end of file".to_string(),
+            ));
         }
 
         Ok(records)
     }
 
-    fn handle_directive(&mut self, line: &str, current_path: &Path) -> Result<(), ZoneParserError> {
-        let tokens: Vec<&str> = line.split_whitespace().collect();
-        match tokens[0].to_uppercase().as_str() {
+    fn handle_directive(
+        &mut self,
+        tokens: &[Token],
+        current_path: &Path,
+    ) -> Result<Vec<Record>, ZoneParserError> {
+        let directive = tokens[0].text;
+        let argument = |index: usize, what: &str| {
+            tokens.get(index).map(|t| t.text).ok_or_else(|| {
+                ZoneParserError::InvalidDirective(format!("{} missing {}", directive, what))
+            })
+        };
+        match directive.to_uppercase().as_str() {
             "$ORIGIN" => {
-                if tokens.len() < 2 {
-                    return Err(ZoneParserError::InvalidDirective(format!(
-                        "$ORIGIN missing value: {}",
-                        line
-                    )));
-                }
-                self.origin = tokens[1].trim_end_matches('.').to_string();
+                self.origin = Name::parse(argument(1, "value")?, Some(&self.origin))?;
             }
             "$TTL" => {
-                if tokens.len() < 2 {
-                    return Err(ZoneParserError::InvalidDirective(format!(
-                        "$TTL missing value: {}",
-                        line
-                    )));
-                }
-                self.ttl = tokens[1].parse().map_err(|_| {
-                    ZoneParserError::InvalidDirective(format!("Invalid TTL value: {}", tokens[1]))
+                let value = argument(1, "value")?;
+                self.ttl = parse_ttl(value).ok_or_else(|| {
+                    ZoneParserError::InvalidDirective(format!("Invalid TTL value: {}", value))
                 })?;
             }
             "$INCLUDE" => {
-                if tokens.len() < 2 {
-                    return Err(ZoneParserError::InvalidDirective(format!(
-                        "$INCLUDE missing file path: {}",
-                        line
-                    )));
-                }
                 let include_path = current_path
                     .parent()
                     .unwrap_or_else(|| Path::new("."))
-                    .join(tokens[1]);
-                let _included_records = self.parse_zone_file(include_path.to_str().unwrap())?;
-            }
-            "$GENERATE" => {
-                todo!()
+                    .join(argument(1, "file path")?);
+                let contents = fs::read_to_string(&include_path)?;
+
+                // An origin given to $INCLUDE only applies to the included file.
+                let origin = self.origin.clone();
+                if let Some(token) = tokens.get(2) {
+                    self.origin = Name::parse(token.text, Some(&origin))?;
+                }
+                let records = self.parse_zone(&contents, &include_path);
+                self.origin = origin;
+                return records;
             }
+            "$GENERATE" => return self.generate(&tokens[1..]),
             _ => {
                 return Err(ZoneParserError::UnknownDirective(format!(
                     "Unknown directive: {}",
-                    tokens[0]
+                    directive
                 )));
             }
         }
-        Ok(())
+        Ok(Vec::new())
     }
-//rewrite better impl here
-    fn parse_record(&self, line: &str) -> Result<Record, ZoneParserError> {
-        let re = Regex::new(
-            r"^(?P<name>\S+)\s+(?P<ttl>\d+)?\s+(?P<class>\S+)?\s+(?P<type>\S+)\s+(?P<data>.+)$",
-        )
-        .unwrap();
-        if let Some(caps) = re.captures(line) {
-            let name = caps.name("name").unwrap().as_str().to_string();
-            let ttl = caps
-                .name("ttl")
-                .map_or(self.ttl, |m| m.as_str().parse().unwrap_or(self.ttl));
-            let class = caps
-                .name("class")
-                .map_or("IN".to_string(), |m| m.as_str().to_string());
-            let record_type = caps.name("type").unwrap().as_str();
-            let data = caps.name("data").unwrap().as_str();
-
-            let rtype = match record_type.to_uppercase().as_str() {
-                "A" => 1,
-                "AAAA" => 28,
-                "CNAME" => 5,
-                "MX" => 15,
-                "NS" => 2,
-                "SOA" => 6,
-                "TXT" => 16,
-                _ => 255,
-            };
-
-            let rdata = match record_type.to_uppercase().as_str() {
-                "A" => {
-                    let ip: std::net::Ipv4Addr = data.parse().unwrap();
-                    RData::A(ip)
-                }
-                "AAAA" => {
-                    let ip: std::net::Ipv6Addr = data.parse().unwrap();
-                    RData::AAAA(ip)
-                }
-                "CNAME" => RData::CNAME(data.to_string()),
-                "MX" => {
-                
-                    let parts: Vec<&str> = data.split_whitespace().collect();
-                    let preference: u16 = parts[0].parse()?;
-                    let exchange = parts[1].to_string();
-                    RData::MX {
-                        preference,
-                        exchange,
+
+    /// Expands `$GENERATE <start>-<stop>[/<step>] <lhs> [<ttl>] [<class>]
+    /// <type> <rhs>` into one record per value in the range, as BIND does.
+    fn generate(&self, tokens: &[Token]) -> Result<Vec<Record>, ZoneParserError> {
+        let range = tokens.first().map(|t| t.text).ok_or_else(|| {
+            ZoneParserError::InvalidDirective("$GENERATE missing range".to_string())
+        })?;
+        if tokens.len() < 4 {
+            return Err(ZoneParserError::InvalidDirective(format!(
+                "$GENERATE missing record: {}",
+                range
+            )));
+        }
+        let invalid_range =
+            || ZoneParserError::InvalidDirective(format!("Invalid $GENERATE range: {}", range));
+        let (bounds, step) = match range.split_once('/') {
+            Some((bounds, step)) => (bounds, step.parse().map_err(|_| invalid_range())?),
+            None => (range, 1),
+        };
+        let (start, stop) = bounds.split_once('-').ok_or_else(invalid_range)?;
+        let start: u32 = start.parse().map_err(|_| invalid_range())?;
+        let stop: u32 = stop.parse().map_err(|_| invalid_range())?;
+        if start > stop || step == 0 {
+            return Err(invalid_range());
+        }
+
+        let mut records = Vec::new();
+        for value in (start..=stop).step_by(step) {
+            let texts = tokens[1..]
+                .iter()
+                .map(|t| substitute(t.text, value))
+                .collect::<Result<Vec<_>, _>>()?;
+            let entry: Vec<Token> = tokens[1..]
+                .iter()
+                .zip(&texts)
+                .map(|(token, text)| Token {
+                    text,
+                    quoted: token.quoted,
+                })
+                .collect();
+            records.push(self.parse_entry(None, &entry)?);
+        }
+        Ok(records)
+    }
+
+    /// Parses a single resource record line in master file format:
+    /// `<owner> [<ttl>] [<class>] <type> <rdata...>`, with TTL and class in
+    /// either order.
+    pub fn parse_record(&self, line: &str) -> Result<Record, ZoneParserError> {
+        let tokens: Vec<Token> = tokenize(line)?
+            .into_iter()
+            .filter(|t| t.quoted || (t.text != "(" && t.text != ")"))
+            .collect();
+        self.parse_entry(None, &tokens)
+    }
+
+    /// Parses the tokens of one entry. The owner name is taken from the
+    /// first token unless `owner` is given.
+    fn parse_entry(
+        &self,
+        owner: Option<Name>,
+        tokens: &[Token],
+    ) -> Result<Record, ZoneParserError> {
+        Ok(text::parse_record(
+            tokens,
+            owner,
+            Some(&self.origin),
+            self.ttl,
+        )?)
+    }
+}
+
+/// Replaces each `$` in a `$GENERATE` template with `value`, honouring
+/// `${offset[,width[,base]]}` modifiers with base `d`, `o`, `x` or `X`.
+/// `$$` and `\$` stand for a literal `$`.
+fn substitute(template: &str, value: u32) -> Result<String, ZoneParserError> {
+    let invalid =
+        || ZoneParserError::InvalidDirective(format!("Invalid $GENERATE template: {}", template));
+    let mut out = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                out.push(c);
+                out.extend(chars.next());
+            }
+            '$' if chars.peek() == Some(&'$') => {
+                chars.next();
+                out.push_str("\\$");
+            }
+            '$' if chars.peek() == Some(&'{') => {
+                chars.next();
+                let mut spec = String::new();
+                loop {
+                    match chars.next().ok_or_else(invalid)? {
+                        '}' => break,
+                        c => spec.push(c),
                     }
                 }
-                "NS" => RData::NS(data.to_string()),
-                "SOA" => {
-               
-                    let parts: Vec<&str> = data.split_whitespace().collect();
-                    let mname = parts[0].to_string();
-                    let rname = parts[1].to_string();
-                    let serial: u32 = parts[2].parse()?;
-                    let refresh: u32 = parts[3].parse()?;
-                    let retry: u32 = parts[4].parse()?;
-                    let expire: u32 = parts[5].parse()?;
-                    let minimum: u32 = parts[6].parse()?;
-                    RData::SOA {
-                        mname,
-                        rname,
-                        serial,
-                        refresh,
-                        retry,
-                        expire,
-                        minimum,
-                    }
+                let mut parts = spec.split(',');
+                let offset: i64 = parts.next().unwrap_or("0").parse().map_err(|_| invalid())?;
+                let width: usize = match parts.next() {
+                    Some(width) => width.parse().map_err(|_| invalid())?,
+                    None => 0,
+                };
+                let base = parts.next().unwrap_or("d");
+                if parts.next().is_some() {
+                    return Err(invalid());
                 }
-                "TXT" => RData::TXT(data.to_string()),
-                _ => RData::Raw(data.as_bytes().to_vec()),
-            };
-
-            Ok(Record {
-                name,
-                rtype,
-                rclass: match class.as_str() {
-                    "IN" => 1,
-                    _ => 255,
-                },
-                ttl,
-                rdata,
-            })
-        } else {
-            Err(ZoneParserError::InvalidRecord(format!(
-                "Failed to parse record: {}",
-                line
-            )))
+                let n = u32::try_from(value as i64 + offset).map_err(|_| invalid())?;
+                let digits = match base {
+                    "d" => format!("{:0width$}", n, width = width),
+                    "o" => format!("{:0width$o}", n, width = width),
+                    "x" => format!("{:0width$x}", n, width = width),
+                    "X" => format!("{:0width$X}", n, width = width),
+                    _ => return Err(invalid()),
+                };
+                out.push_str(&digits);
+            }
+            '$' => out.push_str(&value.to_string()),
+            c => out.push(c),
         }
     }
+    Ok(out)
 }
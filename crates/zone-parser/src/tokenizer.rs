@@ -0,0 +1 @@
+pub use dns_core::text::{tokenize, unescape, Token};
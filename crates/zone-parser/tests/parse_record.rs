@@ -0,0 +1,361 @@
+use dns_core::{Message, Name, RData, RecordClass, RecordType, SvcParam, SvcParamKey};
+use zone_parser::parser::ZoneParser;
+
+fn name(s: &str) -> Name {
+    s.parse().unwrap()
+}
+
+fn parse(line: &str) -> dns_core::Record {
+    let record = ZoneParser::new().parse_record(line).unwrap();
+
+    let mut message = Message::new();
+    message.header.ancount = 1;
+    message.answers.push(record.clone());
+    let bytes = message.to_bytes().unwrap();
+    assert_eq!(Message::from_bytes(&bytes).unwrap().answers[0], record);
+    record
+}
+
+#[test]
+fn parse_srv() {
+    let record = parse("_sip._tcp.example.com. 86400 IN SRV 0 5 5060 sipserver.example.com.");
+    assert_eq!(record.rtype, RecordType::SRV);
+    assert_eq!(record.rclass, RecordClass::IN);
+    assert_eq!(record.ttl, 86400);
+    assert_eq!(
+        record.rdata,
+        RData::SRV {
+            priority: 0,
+            weight: 5,
+            port: 5060,
+            target: name("sipserver.example.com."),
+        }
+    );
+}
+
+#[test]
+fn parse_ptr() {
+    let record = parse("1.2.0.192.in-addr.arpa. IN 3600 PTR host.example.com.");
+    assert_eq!(record.ttl, 3600);
+    assert_eq!(record.rdata, RData::PTR(name("host.example.com.")));
+}
+
+#[test]
+fn parse_naptr() {
+    let record = parse(
+        r#"4.3.2.1.5.5.5.0.0.8.1.e164.arpa. IN NAPTR 100 10 "u" "E2U+sip" "!^.*$!sip:info@example.com!" ."#,
+    );
+    assert_eq!(
+        record.rdata,
+        RData::NAPTR {
+            order: 100,
+            preference: 10,
+            flags: b"u".to_vec(),
+            services: b"E2U+sip".to_vec(),
+            regexp: b"!^.*$!sip:info@example.com!".to_vec(),
+            replacement: Name::root(),
+        }
+    );
+}
+
+#[test]
+fn parse_uri() {
+    let record = parse(r#"_ftp._tcp.example.com. IN URI 10 1 "ftp://ftp1.example.com/public""#);
+    assert_eq!(
+        record.rdata,
+        RData::URI {
+            priority: 10,
+            weight: 1,
+            target: "ftp://ftp1.example.com/public".to_string(),
+        }
+    );
+}
+
+#[test]
+fn missing_field_is_an_error() {
+    assert!(ZoneParser::new()
+        .parse_record("_sip._tcp.example.com. IN SRV 0 5 5060")
+        .is_err());
+}
+
+#[test]
+fn parse_caa() {
+    let record = parse("example.com. 3600 IN CAA 128 issue \"ca.example.net; account=230123\"");
+    match &record.rdata {
+        RData::CAA(caa) => {
+            assert!(caa.issuer_critical());
+            assert_eq!(caa.tag, "issue");
+            assert_eq!(caa.value_str(), Some("ca.example.net; account=230123"));
+        }
+        other => panic!("unexpected rdata {:?}", other),
+    }
+    assert_eq!(
+        record.rdata.to_string(),
+        "128 issue \"ca.example.net; account=230123\""
+    );
+}
+
+#[test]
+fn parse_tlsa_split_hex() {
+    let record = parse(
+        "_25._tcp.mail.example.com. IN TLSA 3 1 1 \
+         d2abde240d7cd3ee6b4b28c54df034b9 7983a1d16e8a410e4561cb106618e971",
+    );
+    match &record.rdata {
+        RData::TLSA(tlsa) => {
+            assert_eq!(tlsa.cert_usage, dns_core::Tlsa::USAGE_DANE_EE);
+            assert_eq!(tlsa.selector, dns_core::Tlsa::SELECTOR_SPKI);
+            assert_eq!(tlsa.matching_type, dns_core::Tlsa::MATCHING_SHA256);
+            assert_eq!(tlsa.cert_data.len(), 32);
+        }
+        other => panic!("unexpected rdata {:?}", other),
+    }
+    assert_eq!(
+        record.rdata.to_string(),
+        "3 1 1 D2ABDE240D7CD3EE6B4B28C54DF034B97983A1D16E8A410E4561CB106618E971"
+    );
+}
+
+#[test]
+fn parse_sshfp_and_openpgpkey() {
+    let record = parse("host.example.com. IN SSHFP 4 2 123456789abcdef67890123456789abcdef67890");
+    assert_eq!(
+        record.rdata.to_string(),
+        "4 2 123456789ABCDEF67890123456789ABCDEF67890"
+    );
+
+    let record = parse("hash._openpgpkey.example.com. IN OPENPGPKEY mQENBFVHm5sBCADq Aw==");
+    assert_eq!(record.rtype, RecordType::OPENPGPKEY);
+    assert_eq!(record.rdata.to_string(), "mQENBFVHm5sBCADqAw==");
+}
+
+#[test]
+fn reject_invalid_security_rdata() {
+    let parser = ZoneParser::new();
+    assert!(parser
+        .parse_record("example.com. IN CAA 0 is-sue \"x\"")
+        .is_err());
+    assert!(parser
+        .parse_record("example.com. IN TLSA 3 1 1 abc")
+        .is_err());
+    assert!(parser
+        .parse_record("example.com. IN OPENPGPKEY abc")
+        .is_err());
+}
+
+#[test]
+fn parse_https_params() {
+    let record = parse(
+        "example.com. 300 IN HTTPS 1 . port=8443 alpn=\"h2,h3\" \
+         ipv4hint=192.0.2.1,192.0.2.2 ech=AEP+DQ== mandatory=port,alpn",
+    );
+    assert_eq!(record.rtype, RecordType::HTTPS);
+    match &record.rdata {
+        RData::HTTPS(svcb) => {
+            assert!(!svcb.is_alias());
+            assert_eq!(
+                svcb.param(SvcParamKey::Alpn),
+                Some(&SvcParam::Alpn(vec![b"h2".to_vec(), b"h3".to_vec()]))
+            );
+            assert_eq!(svcb.param(SvcParamKey::Port), Some(&SvcParam::Port(8443)));
+        }
+        other => panic!("unexpected rdata {:?}", other),
+    }
+    assert_eq!(
+        record.rdata.to_string(),
+        "1 . mandatory=alpn,port alpn=\"h2,h3\" port=8443 \
+         ipv4hint=192.0.2.1,192.0.2.2 ech=AEP+DQ=="
+    );
+}
+
+#[test]
+fn parse_svcb_escaped_alpn() {
+    let record = parse(
+        r#"_8443._foo.api.example.com. IN SVCB 1 foo.example.org. alpn="f\\\\oo\\,bar,h2" key667="hello\210qoo""#,
+    );
+    match &record.rdata {
+        RData::SVCB(svcb) => {
+            assert_eq!(
+                svcb.params,
+                vec![
+                    SvcParam::Alpn(vec![b"f\\oo,bar".to_vec(), b"h2".to_vec()]),
+                    SvcParam::Unknown(667, b"hello\xd2qoo".to_vec()),
+                ]
+            );
+        }
+        other => panic!("unexpected rdata {:?}", other),
+    }
+    assert_eq!(
+        record.rdata.to_string(),
+        r#"1 foo.example.org. alpn="f\\\\oo\\,bar,h2" key667="hello\210qoo""#
+    );
+}
+
+#[test]
+fn reject_invalid_svcb() {
+    let parser = ZoneParser::new();
+    for line in [
+        "example.com. IN HTTPS 1 . alpn=h2 alpn=h3",
+        "example.com. IN HTTPS 1 . mandatory=port alpn=h2",
+        "example.com. IN HTTPS 1 . mandatory=mandatory",
+        "example.com. IN HTTPS 1 . no-default-alpn",
+        "example.com. IN HTTPS 1 . port=http",
+        "example.com. IN HTTPS 1 . key65535=x",
+        "example.com. IN HTTPS 1 . ipv4hint=",
+    ] {
+        assert!(parser.parse_record(line).is_err(), "{}", line);
+    }
+}
+
+#[test]
+fn parse_dnskey_key_tag() {
+    let record = parse(
+        "example. 86400 IN DNSKEY 256 3 5 \
+         AQOeiiR0GOMYkDshWoSKz9XzfwJr1AYtsmx3TGkJaNXVbfi/2pHm822aJ5iI9BMzNXxeYCmZ \
+         DRD99WYwYqUSdjMmmAphXdvxegXd/M5+X7OrzKBaMbCVdFLUUh6DhweJBjEVv5f2wwjM9Xzc \
+         nOf+EPbtG9DMBmADjFDc2w/rljwvFw==",
+    );
+    match &record.rdata {
+        RData::DNSKEY(key) => {
+            assert!(key.is_zone_key());
+            assert!(!key.is_secure_entry_point());
+            assert_eq!(key.key_tag(), 60485);
+        }
+        other => panic!("unexpected rdata {:?}", other),
+    }
+}
+
+#[test]
+fn parse_rrsig_and_ds() {
+    let record = parse(
+        "host.example.com. 86400 IN RRSIG A 5 3 86400 20030322173103 20030220173103 \
+         2642 example.com. oJB1W6WNGv+ldvQ3WDG0MQkg5IEhjRip8WTrPYGv07h108dUKGMeDPKijVCH",
+    );
+    match &record.rdata {
+        RData::RRSIG(rrsig) => {
+            assert_eq!(rrsig.type_covered, RecordType::A);
+            assert_eq!(rrsig.expiration, 1048354263);
+            assert_eq!(rrsig.signer_name, name("example.com."));
+        }
+        other => panic!("unexpected rdata {:?}", other),
+    }
+    assert_eq!(
+        record.rdata.to_string(),
+        "A 5 3 86400 20030322173103 20030220173103 2642 example.com. \
+         oJB1W6WNGv+ldvQ3WDG0MQkg5IEhjRip8WTrPYGv07h108dUKGMeDPKijVCH"
+    );
+
+    let record =
+        parse("dskey.example.com. 86400 IN DS 60485 5 1 2BB183AF5F22588179A53B0A 98631FAD1A292118");
+    assert_eq!(
+        record.rdata.to_string(),
+        "60485 5 1 2BB183AF5F22588179A53B0A98631FAD1A292118"
+    );
+}
+
+#[test]
+fn parse_nsec_and_nsec3() {
+    let record =
+        parse("alfa.example.com. 86400 IN NSEC host.example.com. A MX RRSIG NSEC TYPE1234");
+    match &record.rdata {
+        RData::NSEC(nsec) => {
+            assert!(nsec.types.contains(RecordType::MX));
+            assert!(nsec.types.contains(RecordType::Unknown(1234)));
+            assert!(!nsec.types.contains(RecordType::AAAA));
+        }
+        other => panic!("unexpected rdata {:?}", other),
+    }
+    assert_eq!(
+        record.rdata.to_string(),
+        "host.example.com. A MX RRSIG NSEC TYPE1234"
+    );
+
+    let record = parse(
+        "0p9mhaveqvm6t7vbl5lop2u3t2rp3tom.example. IN NSEC3 1 1 12 aabbccdd \
+         2t7b4g4vsa5smi47k61mv5bv1a22bojr MX DNSKEY NS SOA NSEC3PARAM RRSIG",
+    );
+    match &record.rdata {
+        RData::NSEC3(nsec3) => {
+            assert!(nsec3.opt_out());
+            assert_eq!(nsec3.salt, vec![0xAA, 0xBB, 0xCC, 0xDD]);
+            assert_eq!(nsec3.next_hashed_owner.len(), 20);
+        }
+        other => panic!("unexpected rdata {:?}", other),
+    }
+    assert_eq!(
+        record.rdata.to_string(),
+        "1 1 12 AABBCCDD 2T7B4G4VSA5SMI47K61MV5BV1A22BOJR NS SOA MX RRSIG DNSKEY NSEC3PARAM"
+    );
+
+    let record = parse("example. IN NSEC3PARAM 1 0 0 -");
+    assert_eq!(record.rdata.to_string(), "1 0 0 -");
+}
+
+#[test]
+fn parse_txt_strings() {
+    let record =
+        parse(r#"example.com. IN TXT "v=spf1 -all" "second \"quoted\"" bare\059semi "" "\255""#);
+    assert_eq!(
+        record.rdata,
+        RData::TXT(vec![
+            b"v=spf1 -all".to_vec(),
+            b"second \"quoted\"".to_vec(),
+            b"bare;semi".to_vec(),
+            Vec::new(),
+            vec![0xFF],
+        ])
+    );
+    assert_eq!(
+        record.rdata.to_string(),
+        r#""v=spf1 -all" "second \"quoted\"" "bare;semi" "" "\255""#
+    );
+
+    let long = "k".repeat(600);
+    let record = parse(&format!("dkim._domainkey.example.com. IN TXT \"{}\"", long));
+    match &record.rdata {
+        RData::TXT(strings) => {
+            assert_eq!(
+                strings.iter().map(Vec::len).collect::<Vec<_>>(),
+                [255, 255, 90]
+            );
+            assert_eq!(strings.concat(), long.as_bytes());
+        }
+        other => panic!("unexpected rdata {:?}", other),
+    }
+}
+
+#[test]
+fn parse_generic_rdata() {
+    let record = parse(r"a.example. CLASS32 3600 TYPE731 \# 6 abcd ef012345");
+    assert_eq!(record.rtype, RecordType::Unknown(731));
+    assert_eq!(record.rclass, RecordClass::Unknown(32));
+    assert_eq!(
+        record.rdata,
+        RData::Raw(vec![0xAB, 0xCD, 0xEF, 0x01, 0x23, 0x45])
+    );
+    assert_eq!(record.rdata.to_string(), r"\# 6 ABCDEF012345");
+
+    let record = parse(r"b.example. TYPE62347 \# 0");
+    assert_eq!(record.rdata, RData::Raw(Vec::new()));
+    assert_eq!(record.rdata.to_string(), r"\# 0");
+
+    // Known types given in generic form are decoded into their typed RDATA.
+    let record = parse(r"e.example. IN TYPE1 \# 4 0A000001");
+    assert_eq!(record.rtype, RecordType::A);
+    assert_eq!(record.rdata, RData::A("10.0.0.1".parse().unwrap()));
+}
+
+#[test]
+fn reject_invalid_generic_rdata() {
+    let parser = ZoneParser::new();
+    for line in [
+        r"a.example. TYPE731 \# 4 abcd",
+        r"a.example. TYPE731 \# 1 abc",
+        r"a.example. TYPE731 \# x",
+        r"a.example. TYPE731 abcd",
+        r"a.example. A \# 3 0A0000",
+        r"a.example. A \# 5 0A00000100",
+    ] {
+        assert!(parser.parse_record(line).is_err(), "{}", line);
+    }
+}
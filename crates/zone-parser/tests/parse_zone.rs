@@ -0,0 +1,187 @@
+use dns_core::{Message, Name, RData, RecordType};
+use std::fs;
+use zone_parser::parser::ZoneParser;
+
+fn name(s: &str) -> Name {
+    s.parse().unwrap()
+}
+
+const ZONE: &str = r#"
+$ORIGIN example.com.
+$TTL 1h
+@       IN  SOA ns1 hostmaster (
+                2024010101 ; serial
+                1d         ; refresh
+                2h         ; retry
+                4w         ; expire
+                300 )      ; minimum
+        IN  NS  ns1
+ns1     IN  A   192.0.2.1
+        1h30m IN A 192.0.2.2
+mail    30m IN MX ( 10
+                    mx.example.net. )
+"#;
+
+#[test]
+fn parse_bind_zone() {
+    let records = ZoneParser::new().parse_str(ZONE).unwrap();
+    assert_eq!(records.len(), 5);
+
+    let soa = &records[0];
+    assert_eq!(soa.ttl, 3600);
+    assert_eq!(
+        soa.rdata,
+        RData::SOA {
+            mname: name("ns1.example.com."),
+            rname: name("hostmaster.example.com."),
+            serial: 2024010101,
+            refresh: 86400,
+            retry: 7200,
+            expire: 2419200,
+            minimum: 300,
+        }
+    );
+
+    // Lines starting with whitespace belong to the previous owner.
+    assert_eq!(records[1].name, name("example.com."));
+    assert_eq!(records[3].name, name("ns1.example.com."));
+    assert_eq!(records[3].ttl, 5400);
+
+    assert_eq!(records[4].ttl, 1800);
+    assert_eq!(
+        records[4].rdata,
+        RData::MX {
+            preference: 10,
+            exchange: name("mx.example.net."),
+        }
+    );
+}
+
+const LEGACY_RECORDS: &str = r#"
+$ORIGIN example.com.
+ns1     IN  HINFO "PC-Intel-700mhz" "Linux 6"
+        IN  RP  admin.example.com. info
+office  30m IN LOC 42 21 54 N 71 06 18 W -24m 30m
+old     IN  DNAME new.example.net.
+afs     IN  AFSDB 1 afsdb.example.com.
+cert    IN  CERT PGP 0 0 ( AQID
+                           BAU= )
+@       IN  ZONEMD 2024010101 1 1 (
+    FEBE3D4CE2EC2FFA4BA99D46CD69D6D29711E55217057BEE
+    7EB1A7B641A47BA7FED2DD5B97AE499FAFA4F22C6BD647DE )
+@       IN  CSYNC 66 3 A NS AAAA
+"#;
+
+#[test]
+fn parse_legacy_records() {
+    let records = ZoneParser::new().parse_str(LEGACY_RECORDS).unwrap();
+    assert_eq!(records.len(), 8);
+
+    assert_eq!(
+        records[0].rdata.to_string(),
+        r#""PC-Intel-700mhz" "Linux 6""#
+    );
+    assert_eq!(records[1].name, name("ns1.example.com."));
+    assert_eq!(
+        records[1].rdata,
+        RData::RP {
+            mbox: name("admin.example.com."),
+            txt: name("info.example.com."),
+        }
+    );
+
+    let loc = &records[2];
+    assert_eq!(loc.ttl, 1800);
+    match &loc.rdata {
+        RData::LOC(loc) => {
+            assert_eq!(loc.latitude_millis(), (42 * 3600 + 21 * 60 + 54) * 1000);
+            assert_eq!(loc.longitude_millis(), -(71 * 3600 + 6 * 60 + 18) * 1000);
+            assert_eq!(loc.altitude_cm(), -2400);
+            assert_eq!(loc.size_cm(), 3000);
+            assert_eq!(loc.horiz_pre_cm(), 1_000_000);
+            assert_eq!(loc.vert_pre_cm(), 1000);
+        }
+        other => panic!("unexpected rdata {:?}", other),
+    }
+    assert_eq!(
+        loc.rdata.to_string(),
+        "42 21 54.000 N 71 6 18.000 W -24m 30m 10000m 10m"
+    );
+
+    assert_eq!(records[3].rdata, RData::DNAME(name("new.example.net.")));
+    assert_eq!(records[4].rdata.to_string(), "1 afsdb.example.com.");
+    assert_eq!(records[5].rdata.to_string(), "PGP 0 0 AQIDBAU=");
+    assert_eq!(records[6].rtype, RecordType::ZONEMD);
+    match &records[6].rdata {
+        RData::ZONEMD(zonemd) => assert_eq!(zonemd.digest.len(), 48),
+        other => panic!("unexpected rdata {:?}", other),
+    }
+    assert_eq!(records[7].rdata.to_string(), "66 3 A NS AAAA");
+
+    let mut message = Message::new();
+    message.header.ancount = records.len() as u16;
+    message.answers = records;
+    let bytes = message.to_bytes().unwrap();
+    assert_eq!(Message::from_bytes(&bytes).unwrap(), message);
+}
+
+#[test]
+fn parse_include_with_origin() {
+    let dir = std::env::temp_dir().join(format!("zone-parser-include-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("hosts.db"), "www IN A 192.0.2.80\n").unwrap();
+    fs::write(
+        dir.join("main.db"),
+        "$ORIGIN example.com.\n$INCLUDE hosts.db sub.example.com.\nmail IN A 192.0.2.25\n",
+    )
+    .unwrap();
+
+    let records = ZoneParser::new()
+        .parse_zone_file(dir.join("main.db").to_str().unwrap())
+        .unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].name, name("www.sub.example.com."));
+    assert_eq!(records[1].name, name("mail.example.com."));
+}
+
+#[test]
+fn reject_malformed_entries() {
+    for zone in [
+        "example.com. IN SOA ns1. host. ( 1 2 3 4 5\n",
+        "example.com. IN A 192.0.2.1 )\n",
+        "  IN A 192.0.2.1\n",
+        "example.com. IN LOC 91 0 0 N 0 0 0 E 0m\n",
+        "example.com. 1h30 IN A 192.0.2.1\n",
+    ] {
+        assert!(ZoneParser::new().parse_str(zone).is_err(), "{}", zone);
+    }
+}
+
+#[test]
+fn generate_records() {
+    let zone = "$ORIGIN 2.0.192.in-addr.arpa.\n\
+                $GENERATE 1-5/2 $ 1h PTR host-${10,3,x}.example.com.\n\
+                $GENERATE 7-7 cost$$ IN TXT \"$\"\n";
+    let records = ZoneParser::new().parse_str(zone).unwrap();
+    assert_eq!(records.len(), 4);
+
+    assert_eq!(records[0].name, name("1.2.0.192.in-addr.arpa."));
+    assert_eq!(records[0].ttl, 3600);
+    assert_eq!(records[0].rdata, RData::PTR(name("host-00b.example.com.")));
+    assert_eq!(records[2].name, name("5.2.0.192.in-addr.arpa."));
+    assert_eq!(records[2].rdata, RData::PTR(name("host-00f.example.com.")));
+
+    assert_eq!(records[3].name, name("cost\\$.2.0.192.in-addr.arpa."));
+    assert_eq!(records[3].rdata, RData::TXT(vec![b"7".to_vec()]));
+
+    for zone in [
+        "$GENERATE 5-1 $ PTR host.\n",
+        "$GENERATE 1-5/0 $ PTR host.\n",
+        "$GENERATE 1-5 $ PTR host-${0,2,q}.\n",
+        "$GENERATE 1-5 $\n",
+    ] {
+        assert!(ZoneParser::new().parse_str(zone).is_err(), "{}", zone);
+    }
+}
@@ -0,0 +1,16 @@
+#![no_main]
+
+use dns_core::decompress_name;
+use libfuzzer_sys::fuzz_target;
+use std::io::Cursor;
+
+// The first byte picks the offset to start reading from, so pointers can
+// lead both forwards and backwards.
+fuzz_target!(|data: &[u8]| {
+    let Some((&start, buf)) = data.split_first() else {
+        return;
+    };
+    let mut reader = Cursor::new(buf);
+    reader.set_position(start as u64);
+    let _ = decompress_name(&mut reader);
+});
@@ -0,0 +1,15 @@
+#![no_main]
+
+use dns_core::Message;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(message) = Message::read(&mut &data[..]) else {
+        return;
+    };
+    // Anything we can decode must survive re-encoding unchanged.
+    if let Ok(bytes) = message.to_bytes() {
+        let decoded = Message::from_bytes(&bytes).expect("re-encoded message must decode");
+        assert_eq!(decoded, message);
+    }
+});